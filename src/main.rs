@@ -1,13 +1,154 @@
 #![feature(exclusive_range_pattern)]
+#![cfg_attr(test, feature(test))]
 
 #[macro_use] extern crate arrayref;
-#[macro_use] extern crate lazy_static;
 extern crate sdl2;
+#[cfg(test)] extern crate test;
+
+use std::cell::RefCell;
+use std::env;
+use std::fs::File;
+use std::io::{self, ErrorKind};
+use std::process;
+use std::rc::Rc;
 
 mod nes;
-use nes::Nes;
+use nes::{ErrorPolicy, Nes};
+use nes::cdl::{CdlError, CodeDataLog};
+use nes::cpu::{Profiler, Timing};
+use nes::debugger::{self, Debugger};
+use nes::debugger::gdb::GdbServer;
 
 fn main() {
-    let mut nes = Nes::new("rom/hello_world.nes");
-    nes.run();
+    let args = env::args().collect::<Vec<_>>();
+
+    // --cycle-stepped runs every bus access of the CPU in its own cycle.
+    let timing = if args.iter().any(|arg| arg == "--cycle-stepped") { Timing::CycleStepped } else { Timing::Instruction };
+    let mut nes = Nes::new("rom/hello_world.nes", timing);
+
+    // --cdl FILE logs the used bytes of the cassette, the file is continued if it exists.
+    // a file which can't be continued isn't overwritten, and the emulator doesn't start.
+    let code_data_log = args.iter().position(|arg| arg == "--cdl")
+        .and_then(|i| args.get(i + 1))
+        .map(|path| {
+            let (prg_length, chr_length) = (nes.cassette().program_rom.len(), nes.cassette().character_rom.len());
+            let log = match CodeDataLog::load(path, prg_length, chr_length) {
+                Ok(log) => log,
+                Err(CdlError::IoError(ref err)) if err.kind() == ErrorKind::NotFound => CodeDataLog::new(prg_length, chr_length),
+                Err(error) => {
+                    eprintln!("cdl error: {}: {}", path, error);
+                    process::exit(1);
+                },
+            };
+            (path, Rc::new(RefCell::new(log)))
+        });
+    if let Some((_, ref log)) = code_data_log {
+        nes.set_code_data_log(Some(log.clone()));
+    }
+    // --on-error stop|log|continue chooses what is done on a CPU error, it stops by default.
+    if let Some(i) = args.iter().position(|arg| arg == "--on-error") {
+        let error_policy = match args.get(i + 1).map(|policy| policy.as_str()) {
+            Some("stop") => ErrorPolicy::Stop,
+            Some("log") => ErrorPolicy::Log,
+            Some("continue") => ErrorPolicy::Continue,
+            Some(policy) => {
+                eprintln!("unknown error policy: {}, stop, log or continue is expected", policy);
+                process::exit(1);
+            },
+            None => {
+                eprintln!("error policy is required, stop, log or continue is expected");
+                process::exit(1);
+            },
+        };
+        nes.set_error_policy(error_policy);
+    }
+    // --trace FILE writes every instruction in the format of nestest.log.
+    if let Some(i) = args.iter().position(|arg| arg == "--trace") {
+        let file = match args.get(i + 1).map(|path| (path, File::create(path))) {
+            Some((_, Ok(file))) => file,
+            Some((path, Err(error))) => {
+                eprintln!("trace error: {}: {}", path, error);
+                process::exit(1);
+            },
+            None => {
+                eprintln!("trace error: file is required");
+                process::exit(1);
+            },
+        };
+        nes.set_tracer(Some(Box::new(file)));
+    }
+    // --profile prints the cycles of the subroutines at the exit.
+    if args.iter().any(|arg| arg == "--profile") {
+        nes.cpu_mut().set_profiler(Some(Profiler::new()));
+    }
+
+    let finish = |nes: &Nes| {
+        if let Some((path, ref log)) = code_data_log {
+            if let Err(error) = log.borrow().save(path) {
+                eprintln!("cdl error: {}: {}", path, error);
+            }
+        }
+        if let Some(report) = nes.cpu().profile_report() {
+            eprintln!("{}", report);
+        }
+    };
+    let debugger = |nes| {
+        // --symbols FILE loads ca65 .dbg or FCEUX .nl, it can be repeated.
+        let mut debugger = Debugger::new(nes);
+        for (i, _) in args.iter().enumerate().filter(|&(_, arg)| arg == "--symbols") {
+            if let Some(path) = args.get(i + 1) {
+                if let Err(error) = debugger.load_symbols(path) {
+                    eprintln!("symbol error: {}: {}", path, error);
+                }
+            }
+        }
+        debugger
+    };
+
+    // --gdb PORT waits for a GDB client on the port instead of the screen.
+    if let Some(i) = args.iter().position(|arg| arg == "--gdb") {
+        let port = match args.get(i + 1).map(|port| (port, port.parse::<u16>())) {
+            Some((_, Ok(port))) => port,
+            Some((port, Err(_))) => {
+                eprintln!("gdb error: invalid port: {}", port);
+                process::exit(1);
+            },
+            None => {
+                eprintln!("gdb error: port is required");
+                process::exit(1);
+            },
+        };
+        let server = match GdbServer::bind(port) {
+            Ok(server) => server,
+            Err(error) => {
+                eprintln!("gdb error: port {}: {}", port, error);
+                process::exit(1);
+            },
+        };
+        let mut debugger = debugger(nes);
+        let result = server.serve(&mut debugger);
+        finish(debugger.nes());
+        if let Err(error) = result {
+            eprintln!("gdb error: {}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // --debug starts the debugger on the terminal instead of the screen.
+    if args.iter().any(|arg| arg == "--debug") {
+        let mut debugger = debugger(nes);
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        debugger::repl::run(&mut debugger, stdin.lock(), &mut stdout.lock()).unwrap();
+        finish(debugger.nes());
+        return;
+    }
+
+    let result = nes.run();
+    finish(&nes);
+    if let Err(error) = result {
+        eprintln!("cpu error: {:?}", error);
+        process::exit(1);
+    }
 }
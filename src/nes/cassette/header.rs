@@ -1,5 +1,31 @@
 use super::CassetteInitializeError;
 
+const NAMETABLE_BYTES_LENGTH: u16 = 0x0400;
+
+/// How the cassette wires the 4 nametables of the PPU to VRAM.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mirroring {
+    /// 0x2000 and 0x2400 are the same, 0x2800 and 0x2C00 are the same.
+    Horizontal,
+    /// 0x2000 and 0x2800 are the same, 0x2400 and 0x2C00 are the same.
+    Vertical,
+    /// The cassette has its own VRAM for all nametables.
+    FourScreen,
+}
+
+impl Mirroring {
+    /// Maps `addr` of the nametables from 0x2000 to the address of VRAM.
+    pub fn vram_addr(&self, addr: u16) -> u16 {
+        let nametable_id = (addr / NAMETABLE_BYTES_LENGTH) & 0x03;
+        let page = match *self {
+            Mirroring::Horizontal => nametable_id >> 1,
+            Mirroring::Vertical => nametable_id & 0x01,
+            Mirroring::FourScreen => nametable_id,
+        };
+        page * NAMETABLE_BYTES_LENGTH + addr % NAMETABLE_BYTES_LENGTH
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct INesHeader {
     /// ASCII letters 'NES' followed by 0x1A(EOF)
@@ -8,6 +34,7 @@ pub struct INesHeader {
     pub prg_size: u8,
     /// Number of pages for The character rom
     pub chr_size: u8,
+    pub mirroring: Mirroring,
 }
 
 impl INesHeader {
@@ -16,6 +43,7 @@ impl INesHeader {
         // 0-3: Constant $4E $45 $53 $1A ("NES" followed by MS-DOS end-of-file)
         // 4: Size of PRG ROM in 16 KB units
         // 5: Size of CHR ROM in 8 KB units (Value 0 means the board uses CHR RAM)
+        // 6: Flags 6, bit 0 is the mirroring (0: horizontal, 1: vertical), bit 3 ignores it for four-screen VRAM
         // 
         // refer: https://wiki.nesdev.com/w/index.php/INES
 
@@ -24,10 +52,17 @@ impl INesHeader {
             return Err(CassetteInitializeError::FormatError)
         };
 
+        let mirroring = match buf[6] & 0b00001001 {
+            0b00000000 => Mirroring::Horizontal,
+            0b00000001 => Mirroring::Vertical,
+            _ => Mirroring::FourScreen,
+        };
+
         Ok(INesHeader {
             magic_numbers: magic_numbers,
             prg_size: buf[4],
             chr_size: buf[5],
+            mirroring: mirroring,
         })
     }
 }
@@ -38,9 +73,9 @@ mod ines_header_test {
 
     #[test]
     fn new_success() {
-        // "N" "E" "S" "\x1A" "5" "3"
-        let rom_bytes = [78, 69, 83, 26, 53, 51];
-        assert_eq!(rom_bytes, *"NES\x1A53".as_bytes());
+        // "N" "E" "S" "\x1A" "5" "3" "\x01"
+        let rom_bytes = [78, 69, 83, 26, 53, 51, 1];
+        assert_eq!(rom_bytes, *"NES\x1A53\x01".as_bytes());
 
         let ines_header = INesHeader::new(&rom_bytes.to_vec()).unwrap();
         assert_eq!(ines_header, INesHeader {
@@ -52,9 +87,21 @@ mod ines_header_test {
             ],
             prg_size: rom_bytes[4],
             chr_size: rom_bytes[5],
+            mirroring: Mirroring::Vertical,
         });
     }
 
+    #[test]
+    fn mirroring_test() {
+        assert_eq!(Mirroring::Horizontal.vram_addr(0x0412), 0x0012);
+        assert_eq!(Mirroring::Horizontal.vram_addr(0x0C12), 0x0412);
+        assert_eq!(Mirroring::Vertical.vram_addr(0x0812), 0x0012);
+        assert_eq!(Mirroring::Vertical.vram_addr(0x0C12), 0x0412);
+        assert_eq!(Mirroring::FourScreen.vram_addr(0x0C12), 0x0C12);
+        // 0x3000-0x3EFF is the mirror of 0x2000-0x2EFF.
+        assert_eq!(Mirroring::Vertical.vram_addr(0x1412), 0x0412);
+    }
+
     #[test]
     fn new_format_error() {
         // "N" "N" "S" "\x1A" "5" "3"
@@ -0,0 +1,186 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+
+use nes::cpu::{Access, AccessKind, BusObserver};
+
+// flags of the program rom
+pub const CODE: u8 = 0x01;
+pub const DATA: u8 = 0x02;
+// flags of the character rom
+pub const RENDERED: u8 = 0x01;
+pub const READ: u8 = 0x02;
+
+/// Code/Data Logger: how every byte of the program rom and the character rom has been used.
+///
+/// The flags are compatible with FCEUX `.cdl`, which is the program rom flags followed by the character rom flags.
+/// Bits 2-3 of the program rom flags are the 8KB window of 0x8000-0xFFFF which the byte was accessed through.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CodeDataLog {
+    prg: Vec<u8>,
+    chr: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum CdlError {
+    IoError(io::Error),
+    /// The size of the file doesn't match the cassette.
+    SizeMismatch { expected: usize, actual: usize },
+}
+
+impl From<io::Error> for CdlError {
+    fn from(err: io::Error) -> Self {
+        CdlError::IoError(err)
+    }
+}
+
+impl fmt::Display for CdlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CdlError::IoError(ref err) => write!(f, "{}", err),
+            CdlError::SizeMismatch { expected, actual } => write!(f, "the size is {} bytes, but the cassette needs {} bytes", actual, expected),
+        }
+    }
+}
+
+impl CodeDataLog {
+    pub fn new(program_rom_length: usize, character_rom_length: usize) -> Self {
+        Self {
+            prg: vec![0; program_rom_length],
+            chr: vec![0; character_rom_length],
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8], program_rom_length: usize, character_rom_length: usize) -> Result<Self, CdlError> {
+        let expected = program_rom_length + character_rom_length;
+        if bytes.len() != expected {
+            return Err(CdlError::SizeMismatch { expected: expected, actual: bytes.len() });
+        }
+
+        let (prg, chr) = bytes.split_at(program_rom_length);
+        Ok(Self { prg: prg.to_vec(), chr: chr.to_vec() })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.prg.clone();
+        bytes.extend_from_slice(&self.chr);
+        bytes
+    }
+
+    pub fn load(path: &str, program_rom_length: usize, character_rom_length: usize) -> Result<Self, CdlError> {
+        let mut bytes = vec![];
+        File::open(path)?.read_to_end(&mut bytes)?;
+        CodeDataLog::from_bytes(&bytes, program_rom_length, character_rom_length)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), CdlError> {
+        File::create(path)?.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn prg(&self) -> &[u8] {
+        &self.prg
+    }
+
+    pub fn chr(&self) -> &[u8] {
+        &self.chr
+    }
+
+    /// Marks the byte of the program rom at `offset`, which the CPU accessed at `addr`.
+    pub fn log_prg(&mut self, offset: usize, addr: u16, flag: u8) {
+        if let Some(byte) = self.prg.get_mut(offset) {
+            let window = ((addr >> 13) & 0x03) as u8;
+            *byte |= flag | window << 2;
+        }
+    }
+
+    /// Marks `len` bytes of the character rom from `offset`.
+    pub fn log_chr(&mut self, offset: usize, len: usize, flag: u8) {
+        let end = (offset + len).min(self.chr.len());
+        let start = offset.min(end);
+        for byte in &mut self.chr[start..end] {
+            *byte |= flag;
+        }
+    }
+}
+
+/// The program rom is marked by observing the CPU bus.
+impl BusObserver for CodeDataLog {
+    fn observe(&mut self, access: &Access) {
+        let flag = match access.kind {
+            AccessKind::Fetch => CODE,
+            AccessKind::Read => DATA,
+            AccessKind::Write => return,
+        };
+
+        // 16KB program rom is mirrored to 0xC000-0xFFFF.
+        if access.addr >= 0x8000 && !self.prg.is_empty() {
+            let offset = (access.addr - 0x8000) as usize % self.prg.len();
+            self.log_prg(offset, access.addr, flag);
+        }
+    }
+}
+
+#[cfg(test)]
+mod cdl_test {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn log_test() {
+        let mut log = CodeDataLog::new(0x8000, 0x2000);
+        log.log_prg(0x0000, 0x8000, CODE);
+        log.log_prg(0x0000, 0x8000, DATA);
+        log.log_prg(0x7FFC, 0xFFFC, DATA);
+        // out of the rom is ignored.
+        log.log_prg(0x8000, 0x0000, CODE);
+        log.log_chr(0x1FF8, 16, RENDERED);
+
+        assert_eq!(log.prg()[0x0000], CODE | DATA);
+        assert_eq!(log.prg()[0x7FFC], DATA | 0x0C);
+        assert_eq!(log.chr()[0x1FF7], 0x00);
+        assert_eq!(log.chr()[0x1FFF], RENDERED);
+    }
+
+    #[test]
+    fn observe_test() {
+        let mut log = CodeDataLog::new(0x4000, 0);
+        log.observe(&Access { addr: 0xC010, data: 0xEA, kind: AccessKind::Fetch, cycle: 0 });
+        log.observe(&Access { addr: 0x8020, data: 0x00, kind: AccessKind::Read, cycle: 0 });
+        log.observe(&Access { addr: 0x0300, data: 0x00, kind: AccessKind::Read, cycle: 0 });
+
+        assert_eq!(log.prg()[0x0010], CODE | 0x08);
+        assert_eq!(log.prg()[0x0020], DATA);
+        assert_eq!(log.prg().iter().filter(|flags| **flags != 0).count(), 2);
+    }
+
+    #[test]
+    fn save_and_load_test() {
+        let mut log = CodeDataLog::new(0x4000, 0x2000);
+        log.log_prg(0x0010, 0xC010, CODE);
+        log.log_chr(0x0100, 1, READ);
+
+        let path = env::temp_dir().join(format!("nes_cdl_test_{}.cdl", process::id()));
+        let path = path.to_str().unwrap();
+        log.save(path).unwrap();
+        let loaded = CodeDataLog::load(path, 0x4000, 0x2000);
+        let mismatched = CodeDataLog::load(path, 0x8000, 0x2000);
+        fs::remove_file(path).unwrap();
+
+        let bytes = log.to_bytes();
+        assert_eq!(bytes.len(), 0x6000);
+        assert_eq!(bytes[0x0010], CODE | 0x08);
+        assert_eq!(bytes[0x4100], READ);
+        assert_eq!(loaded.unwrap(), log);
+
+        match mismatched {
+            Err(error @ CdlError::SizeMismatch { expected: 0xA000, actual: 0x6000 }) => {
+                assert_eq!(error.to_string(), "the size is 24576 bytes, but the cassette needs 40960 bytes");
+            },
+            result => panic!("unexpected {:?}", result),
+        }
+    }
+}
@@ -1,75 +1,204 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use nes::cassette::NesCassette;
-use nes::ppu::Ppu;
+use nes::cpu::observer::{Access, AccessKind, BusObserver};
+use nes::ppu::{Ppu, PpuRunResult};
 use nes::ram::Ram;
+use nes::cpu::open_bus::OpenBus;
 
 pub struct Bus<'a, T: 'a> where T: NesCassette {
     cassette: &'a T,
     ppu: &'a mut Ppu,
     wram: &'a mut Ram,
+    open_bus: &'a mut OpenBus,
+    has_built_background: bool,
+    observers: &'a [Rc<RefCell<dyn BusObserver>>],
+    cycle: usize,
+    ticks: usize,
+    dma_stall: usize,
 }
 
 pub trait CpuBus {
     fn read(&mut self, addr: u16) -> u8;
     fn read_twice(&mut self, addr: u16) -> u16;
     fn write(&mut self, addr: u16, data: u8);
+
+    /// Reads the opecode or the operand of the instruction at PC.
+    fn fetch(&mut self, addr: u16) -> u8 {
+        self.read(addr)
+    }
+
+    /// Reads without side effects, for debugging tools like the disassembler.
+    fn peek(&mut self, addr: u16) -> u8 {
+        self.read(addr)
+    }
+
+    /// A read whose data is thrown away, like the read of the unfixed address by indexed addressing.
+    /// It is performed only by the cycle-stepped CPU.
+    fn dummy_read(&mut self, _addr: u16) {}
+
+    /// The write of the unmodified data by read-modify-write instructions.
+    /// It is performed only by the cycle-stepped CPU.
+    fn dummy_write(&mut self, _addr: u16, _data: u8) {}
+
+    /// Called once per CPU cycle by the cycle-stepped CPU, before the bus access of the cycle.
+    fn tick(&mut self) {}
+
+    /// Returns the scanline and the dot of the PPU, for tracing.
+    fn ppu_position(&self) -> (usize, usize) {
+        (0, 0)
+    }
 }
 
 impl <'a, T: 'a> Bus<'a, T> where T: NesCassette {
-    pub fn new(cassette: &'a T, ppu: &'a mut Ppu, wram: &'a mut Ram) -> Self {
+    pub fn new(cassette: &'a T, ppu: &'a mut Ppu, wram: &'a mut Ram, open_bus: &'a mut OpenBus) -> Self {
         Self {
             cassette: cassette,
             ppu: ppu,
             wram: wram,
+            open_bus: open_bus,
+            has_built_background: false,
+            observers: &[],
+            cycle: 0,
+            ticks: 0,
+            dma_stall: 0,
+        }
+    }
+
+    /// Sets the observers of the accesses, `cycle` is the CPU cycle where the instruction starts.
+    pub fn set_observers(&mut self, observers: &'a [Rc<RefCell<dyn BusObserver>>], cycle: usize) {
+        self.observers = observers;
+        self.cycle = cycle;
+    }
+
+    /// Whether the PPU finished all background lines while the bus was ticked.
+    pub fn has_built_background(&self) -> bool {
+        self.has_built_background
+    }
+
+    /// The CPU cycles which the CPU is stalled for by the DMA since the bus was created.
+    pub fn dma_stall(&self) -> usize {
+        self.dma_stall
+    }
+
+    /// Copies the page `page` to OAM through OAMDATA, the reads are observed like the reads of the CPU.
+    /// It takes 513 cycles, and 1 more to align to the read cycle when it starts on an odd cycle.
+    fn oam_dma(&mut self, page: u8) {
+        let start = (page as u16) << 8;
+        for offset in 0..0x100 {
+            let data = self.read(start + offset);
+            self.ppu.write(0x0004, data);
+        }
+
+        let cycle = self.cycle + self.ticks.saturating_sub(1);
+        self.dma_stall += 513 + cycle % 2;
+    }
+
+    fn read_program_rom(&self, addr: u16) -> u8 {
+        // 16KB program rom is mirrored to 0xC000-0xFFFF.
+        let offset = addr - 0x8000;
+        if self.cassette.program_rom_length() <= 0x4000 {
+            self.cassette.read_program_rom(offset & 0x3FFF)
+        } else {
+            self.cassette.read_program_rom(offset)
         }
     }
+
+    fn notify(&self, addr: u16, data: u8, kind: AccessKind) {
+        // the access of the cycle-stepped CPU is in the last ticked cycle.
+        let cycle = self.cycle + self.ticks.saturating_sub(1);
+        let access = Access { addr: addr, data: data, kind: kind, cycle: cycle };
+        for observer in self.observers {
+            observer.borrow_mut().observe(&access);
+        }
+    }
+
+    fn read_mapped(&mut self, addr: u16) -> u8 {
+        let data = match addr {
+            // 0x0800-0x1FFF is the mirror of 0x0000-0x07FF
+            0x0000..0x2000 => self.wram.read(addr & 0x07FF),
+            // 0x2008-0x3FFF is the mirror of 0x2000-0x2007
+            0x2000..0x4000 => self.ppu.read(addr & 0x0007),
+            // APU status is read inside the CPU, the data bus isn't driven.
+            // APU isn't emulated yet, only bit 5 (open bus) is returned.
+            0x4015 => return self.open_bus.latch() & 0x20,
+            // controllers drive only bits 0-4, and they aren't connected yet.
+            0x4016 | 0x4017 => return self.open_bus.drive_partially(0x00, 0x1F),
+            // write only APU and I/O registers
+            0x4000..0x4020 => return self.open_bus.latch(),
+            // expansion rom and ram aren't on NROM, nothing drives the bus.
+            0x4020..0x8000 => return self.open_bus.latch(),
+            0x8000..=0xFFFF => self.read_program_rom(addr),
+        };
+
+        self.open_bus.drive(data)
+    }
 }
 
 impl <'a, T: 'a> CpuBus for Bus<'a, T> where T: NesCassette {
     fn read(&mut self, addr: u16) -> u8 {
-        match addr {
-            0x0000..0x07FF => self.wram.read(addr),
-            0x0800..0x1FFF => self.wram.read(addr - 0x0800),
-            0x2000..0x2007 => self.ppu.read(addr),
-            0x2008..0x3FFF => self.ppu.read(addr - 0x2008),
-            // 0x4000..0x401F => unimplemented!(), // APU I/O Keypad
-            // 0x4020..0x5FFF => unimplemented!(), // Expantion Rom
-            // 0x6000..0x7FFF => unimplemented!(), // Expantion Ram
-            0x8000..0xBFFF => {
-                // ToDo: refactoring
-                if self.cassette.program_rom_length() <= 0x4000 {
-                    self.cassette.read_program_rom(addr - 0xC000)
-                } else {
-                    self.cassette.read_program_rom(addr - 0x8000)
-                }
-            },
-            0xC000..0xFFFF => self.cassette.read_program_rom(addr - 0x8000),
-            _ => panic!("unexpected memory area access!"),
-        }
+        let data = self.read_mapped(addr);
+        self.notify(addr, data, AccessKind::Read);
+        data
+    }
+
+    fn fetch(&mut self, addr: u16) -> u8 {
+        let data = self.read_mapped(addr);
+        self.notify(addr, data, AccessKind::Fetch);
+        data
     }
 
     fn read_twice(&mut self, addr: u16) -> u16 {
         let lower = self.read(addr) as u16;
-        let upper = self.read(addr + 1) as u16;
+        let upper = self.read(addr.wrapping_add(1)) as u16;
 
         (upper << 8) | lower
     }
 
     fn write(&mut self, addr: u16, data: u8) {
+        self.open_bus.drive(data);
+        self.notify(addr, data, AccessKind::Write);
+
         match addr {
-            0x0000..0x07FF => self.wram.write(addr, data),
-            0x0800..0x1FFF => self.wram.write(addr - 0x0800, data),
-            0x2000..0x3FFF => self.ppu.write(addr - 0x2000, data),
-            // 0x4000..0x401F => unimplemented!(), // APU I/O Keypad
-            // 0x4020..0x5FFF => unimplemented!(), // Expantion Rom
-            // 0x6000..0x7FFF => unimplemented!(), // Expantion Ram
-            _ => panic!("unexpected memory area access!"),
+            0x0000..0x2000 => self.wram.write(addr & 0x07FF, data),
+            0x2000..0x4000 => self.ppu.write(addr & 0x0007, data),
+            0x4014 => self.oam_dma(data),
+            // APU and I/O aren't emulated yet
+            0x4000..0x4020 => {},
+            // expansion rom and ram aren't on NROM, and the program rom can't be written.
+            // games write to the program rom to switch the banks of mappers, NROM ignores it.
+            0x4020..=0xFFFF => {},
+        }
+    }
+
+    fn tick(&mut self) {
+        self.ticks += 1;
+
+        // PPU runs 3 times as fast as CPU.
+        if let PpuRunResult::FinishedBuildAllBackgroundLine = self.ppu.run(3) {
+            self.has_built_background = true;
         }
     }
+
+    fn peek(&mut self, addr: u16) -> u8 {
+        // registers are not read, because reading them changes their state.
+        match addr {
+            0x0000..0x2000 => self.wram.read(addr & 0x07FF),
+            0x8000..=0xFFFF => self.read_program_rom(addr),
+            _ => self.open_bus.latch(),
+        }
+    }
+
+    fn ppu_position(&self) -> (usize, usize) {
+        (self.ppu.line, self.ppu.cycle)
+    }
 }
 
 #[cfg(test)]
 mod cpu_bus_test {
     use super::*;
+    use nes::cassette::header::Mirroring;
 
     struct CassetteMock {
         program_rom: Vec<u8>,
@@ -102,14 +231,16 @@ mod cpu_bus_test {
     #[test]
     fn read_from_wram_address() {
         let cassette = CassetteMock::new();
-        let mut ppu = Ppu::new(cassette.character_rom.clone());
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
         let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
         ram.write(0x0002, 0x4F);
 
         let mut cpu_bus = Bus::new(
             &cassette,
             &mut ppu,
             &mut ram,
+            &mut open_bus,
         );
 
         assert_eq!(cpu_bus.read(0x0002), 0x4F);
@@ -118,14 +249,16 @@ mod cpu_bus_test {
     #[test]
     fn read_from_program_rom_address() {
         let mut cassette = CassetteMock::new();
-        let mut ppu = Ppu::new(cassette.character_rom.clone());
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
         let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
         cassette.program_rom[0x0000] = 0x78;
 
         let mut cpu_bus = Bus::new(
             &cassette,
             &mut ppu,
             &mut ram,
+            &mut open_bus,
         );
 
         assert_eq!(cpu_bus.read(0x8000), 0x78);
@@ -134,12 +267,14 @@ mod cpu_bus_test {
     #[test]
     fn write_wram_address() {
         let cassette = CassetteMock::new();
-        let mut ppu = Ppu::new(cassette.character_rom.clone());
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
         let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
         let mut cpu_bus = Bus::new(
             &cassette,
             &mut ppu,
             &mut ram,
+            &mut open_bus,
         );
 
         cpu_bus.write(0x0002, 0x4F);
@@ -149,8 +284,9 @@ mod cpu_bus_test {
     #[test]
     fn read_twice_test() {
         let mut cassette = CassetteMock::new();
-        let mut ppu = Ppu::new(cassette.character_rom.clone());
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
         let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
         cassette.program_rom[0x0000] = 0x78;
         cassette.program_rom[0x0001] = 0x56;
 
@@ -158,8 +294,174 @@ mod cpu_bus_test {
             &cassette,
             &mut ppu,
             &mut ram,
+            &mut open_bus,
         );
 
         assert_eq!(cpu_bus.read_twice(0x8000), 0x5678);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn read_mirrored_wram_address() {
+        let cassette = CassetteMock::new();
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
+        let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
+        ram.write(0x07FF, 0x4F);
+
+        let mut cpu_bus = Bus::new(
+            &cassette,
+            &mut ppu,
+            &mut ram,
+            &mut open_bus,
+        );
+
+        assert_eq!(cpu_bus.read(0x07FF), 0x4F);
+        assert_eq!(cpu_bus.read(0x1FFF), 0x4F);
+    }
+
+    #[test]
+    fn read_mirrored_program_rom_address() {
+        let mut cassette = CassetteMock::new();
+        cassette.program_rom = vec![0; 0x4000];
+        cassette.program_rom[0x3FFC] = 0x78;
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
+        let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
+
+        let mut cpu_bus = Bus::new(
+            &cassette,
+            &mut ppu,
+            &mut ram,
+            &mut open_bus,
+        );
+
+        assert_eq!(cpu_bus.read(0xBFFC), 0x78);
+        assert_eq!(cpu_bus.read(0xFFFC), 0x78);
+    }
+
+    #[test]
+    fn write_program_rom_address() {
+        let cassette = CassetteMock::new();
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
+        let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
+        let mut cpu_bus = Bus::new(
+            &cassette,
+            &mut ppu,
+            &mut ram,
+            &mut open_bus,
+        );
+
+        // the write is ignored.
+        cpu_bus.write(0x8000, 0x01);
+        cpu_bus.write(0x6000, 0x01);
+        assert_eq!(cpu_bus.read(0x8000), 0x00);
+    }
+
+    struct ObserverMock {
+        accesses: Vec<Access>,
+    }
+
+    impl BusObserver for ObserverMock {
+        fn observe(&mut self, access: &Access) {
+            self.accesses.push(*access);
+        }
+    }
+
+    #[test]
+    fn observe_accesses() {
+        let mut cassette = CassetteMock::new();
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
+        let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
+        cassette.program_rom[0x0000] = 0xEA;
+
+        let observer = Rc::new(RefCell::new(ObserverMock { accesses: vec![] }));
+        let observers: Vec<Rc<RefCell<dyn BusObserver>>> = vec![observer.clone()];
+        {
+            let mut cpu_bus = Bus::new(&cassette, &mut ppu, &mut ram, &mut open_bus);
+            cpu_bus.set_observers(&observers, 100);
+            cpu_bus.fetch(0x8000);
+            cpu_bus.write(0x0010, 0x12);
+            // the cycle-stepped CPU ticks the bus before the access.
+            cpu_bus.tick();
+            cpu_bus.tick();
+            cpu_bus.read(0x0810);
+            cpu_bus.peek(0x0010);
+        }
+
+        assert_eq!(observer.borrow().accesses, vec![
+            Access { addr: 0x8000, data: 0xEA, kind: AccessKind::Fetch, cycle: 100 },
+            Access { addr: 0x0010, data: 0x12, kind: AccessKind::Write, cycle: 100 },
+            Access { addr: 0x0810, data: 0x12, kind: AccessKind::Read, cycle: 101 },
+        ]);
+    }
+
+    #[test]
+    fn oam_dma() {
+        let cassette = CassetteMock::new();
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
+        let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
+        for offset in 0..0x100 {
+            ram.write(0x0200 + offset, offset as u8);
+        }
+
+        let observer = Rc::new(RefCell::new(ObserverMock { accesses: vec![] }));
+        let observers: Vec<Rc<RefCell<dyn BusObserver>>> = vec![observer.clone()];
+        {
+            let mut cpu_bus = Bus::new(&cassette, &mut ppu, &mut ram, &mut open_bus);
+            cpu_bus.set_observers(&observers, 0);
+            // OAMADDR is the start of the copy.
+            cpu_bus.write(0x2003, 0x10);
+            cpu_bus.write(0x4014, 0x02);
+            assert_eq!(cpu_bus.dma_stall(), 513);
+
+            cpu_bus.tick();
+            cpu_bus.tick();
+            cpu_bus.write(0x4014, 0x02);
+            assert_eq!(cpu_bus.dma_stall(), 513 + 514);
+        }
+
+        assert_eq!(ppu.context.oam.read(0x10), 0x00);
+        assert_eq!(ppu.context.oam.read(0x0F), 0xFF);
+        assert_eq!(ppu.registers.oam_addr.read(), 0x10);
+
+        // the reads of the DMA are observed.
+        let accesses = &observer.borrow().accesses;
+        let reads = accesses.iter().filter(|access| access.kind == AccessKind::Read).collect::<Vec<_>>();
+        assert_eq!(reads.len(), 0x200);
+        assert_eq!(*reads[0xFF], Access { addr: 0x02FF, data: 0xFF, kind: AccessKind::Read, cycle: 0 });
+    }
+
+    #[test]
+    fn read_open_bus() {
+        let mut cassette = CassetteMock::new();
+        let mut ppu = Ppu::new(cassette.character_rom.clone(), Mirroring::Horizontal);
+        let mut ram = Ram::new(vec![0; 2048]);
+        let mut open_bus = OpenBus::new();
+        cassette.program_rom[0x0002] = 0x40;
+
+        let mut cpu_bus = Bus::new(
+            &cassette,
+            &mut ppu,
+            &mut ram,
+            &mut open_bus,
+        );
+
+        // LDA $4000: the upper byte of the operand is left on the bus.
+        cpu_bus.read(0x8002);
+        assert_eq!(cpu_bus.read(0x4000), 0x40);
+        assert_eq!(cpu_bus.read(0x6000), 0x40);
+
+        // the controller port drives only the lower bits.
+        assert_eq!(cpu_bus.read(0x4016), 0x40);
+        cpu_bus.write(0x0000, 0xFF);
+        assert_eq!(cpu_bus.read(0x4017), 0xE0);
+
+        // APU status doesn't drive the bus.
+        cpu_bus.write(0x0000, 0xFF);
+        assert_eq!(cpu_bus.read(0x4015), 0x20);
+        assert_eq!(cpu_bus.read(0x4000), 0xFF);
+    }
+}
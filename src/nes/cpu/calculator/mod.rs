@@ -1,17 +1,40 @@
 use nes::cpu::bus::CpuBus;
 use nes::cpu::registers::Registers;
-use nes::cpu::controller::Controller;
-use nes::cpu::opecode::{Command, OPECODE_MAP, AddressingMode};
+use nes::cpu::controller::{Controller, IRQ_VECTOR};
+use nes::cpu::opecode::{self, Command, AddressingMode};
+use nes::cpu::variant::Variant;
+use nes::cpu::state::CpuState;
+use nes::cpu::error::CpuError;
+
+#[cfg(feature = "illegal-opcodes")]
+mod unofficial;
+
+// only the opecode has been fetched.
+const UNKNOWN_OPECODE_CYCLE: usize = 1;
 
 pub struct Calculator;
 
 impl Calculator {
-    pub fn execute<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> usize {
+    /// Executes 1 instruction and returns the spent cycle, the error is returned with the cycle spent until it.
+    pub fn execute<T: CpuBus>(registers: &mut Registers, bus: &mut T, variant: &Variant, state: &mut CpuState) -> Result<usize, (CpuError, usize)> {
+        let pc = registers.PC;
         let run_opecode = Controller::fetch(registers, bus);
-        let opecode_rule = OPECODE_MAP.get(&run_opecode).unwrap();
+        let opecode_rule = match opecode::decode(run_opecode) {
+            Some(rule) => rule,
+            None => return Err((CpuError::UnknownOpecode { pc: pc, opecode: run_opecode }, UNKNOWN_OPECODE_CYCLE)),
+        };
 
         let (command, mode, cycle) = (&opecode_rule.command, &opecode_rule.mode, opecode_rule.cycle);
-        let opeland = Controller::fetch_opeland(registers, bus, &mode);
+        let (opeland, page_crossed) = Controller::fetch_opeland(registers, bus, &mode);
+        let mut branch_cycle = 0;
+
+        // read instructions read the unfixed address only when the page is crossed,
+        // store and read-modify-write instructions always read it.
+        if mode.is_indexed() && (page_crossed || !command.has_page_cross_penalty()) {
+            Controller::read_unfixed_address(bus, opeland, page_crossed);
+        }
+
+        let next_pc = registers.PC;
 
         match *command {
             Command::LDA if *mode == AddressingMode::Immediate => Calculator::LDA_immediate(registers, opeland),
@@ -21,15 +44,113 @@ impl Calculator {
             Command::LDY if *mode == AddressingMode::Immediate => Calculator::LDY_immediate(registers, opeland),
             Command::LDY => Calculator::LDY(registers, bus, opeland),
             Command::STA => Calculator::STA(registers, bus, opeland),
-            Command::BNE => Calculator::BNE(registers, opeland),
-            Command::DEY => Calculator::DEY(registers),
+            Command::STX => Calculator::STX(registers, bus, opeland),
+            Command::STY => Calculator::STY(registers, bus, opeland),
+            Command::TAX => Calculator::TAX(registers),
+            Command::TAY => Calculator::TAY(registers),
+            Command::TSX => Calculator::TSX(registers),
+            Command::TXA => Calculator::TXA(registers),
+            Command::TXS => Calculator::TXS(registers),
+            Command::TYA => Calculator::TYA(registers),
+            Command::ADC if *mode == AddressingMode::Immediate => Calculator::ADC_immediate(registers, opeland, variant),
+            Command::ADC => Calculator::ADC(registers, bus, opeland, variant),
+            Command::SBC if *mode == AddressingMode::Immediate => Calculator::SBC_immediate(registers, opeland, variant),
+            Command::SBC => Calculator::SBC(registers, bus, opeland, variant),
+            Command::AND if *mode == AddressingMode::Immediate => Calculator::AND_immediate(registers, opeland),
+            Command::AND => Calculator::AND(registers, bus, opeland),
+            Command::ORA if *mode == AddressingMode::Immediate => Calculator::ORA_immediate(registers, opeland),
+            Command::ORA => Calculator::ORA(registers, bus, opeland),
+            Command::EOR if *mode == AddressingMode::Immediate => Calculator::EOR_immediate(registers, opeland),
+            Command::EOR => Calculator::EOR(registers, bus, opeland),
+            Command::BIT => Calculator::BIT(registers, bus, opeland),
+            Command::CMP if *mode == AddressingMode::Immediate => Calculator::CMP_immediate(registers, opeland),
+            Command::CMP => Calculator::CMP(registers, bus, opeland),
+            Command::CPX if *mode == AddressingMode::Immediate => Calculator::CPX_immediate(registers, opeland),
+            Command::CPX => Calculator::CPX(registers, bus, opeland),
+            Command::CPY if *mode == AddressingMode::Immediate => Calculator::CPY_immediate(registers, opeland),
+            Command::CPY => Calculator::CPY(registers, bus, opeland),
+            Command::ASL if *mode == AddressingMode::Accumulator => Calculator::ASL_accumulator(registers),
+            Command::ASL => Calculator::ASL(registers, bus, opeland),
+            Command::LSR if *mode == AddressingMode::Accumulator => Calculator::LSR_accumulator(registers),
+            Command::LSR => Calculator::LSR(registers, bus, opeland),
+            Command::ROL if *mode == AddressingMode::Accumulator => Calculator::ROL_accumulator(registers),
+            Command::ROL => Calculator::ROL(registers, bus, opeland),
+            Command::ROR if *mode == AddressingMode::Accumulator => Calculator::ROR_accumulator(registers),
+            Command::ROR => Calculator::ROR(registers, bus, opeland),
+            Command::INC => Calculator::INC(registers, bus, opeland),
             Command::INX => Calculator::INX(registers),
+            Command::INY => Calculator::INY(registers),
+            Command::DEC => Calculator::DEC(registers, bus, opeland),
+            Command::DEX => Calculator::DEX(registers),
+            Command::DEY => Calculator::DEY(registers),
+            Command::BCC => branch_cycle = Calculator::BCC(registers, opeland),
+            Command::BCS => branch_cycle = Calculator::BCS(registers, opeland),
+            Command::BEQ => branch_cycle = Calculator::BEQ(registers, opeland),
+            Command::BMI => branch_cycle = Calculator::BMI(registers, opeland),
+            Command::BNE => branch_cycle = Calculator::BNE(registers, opeland),
+            Command::BPL => branch_cycle = Calculator::BPL(registers, opeland),
+            Command::BVC => branch_cycle = Calculator::BVC(registers, opeland),
+            Command::BVS => branch_cycle = Calculator::BVS(registers, opeland),
             Command::JMP => Calculator::JMP(registers, opeland),
+            Command::JSR => Calculator::JSR(registers, bus, opeland),
+            Command::RTS => Calculator::RTS(registers, bus),
+            Command::BRK => Calculator::BRK(registers, bus),
+            Command::RTI => Calculator::RTI(registers, bus),
+            Command::PHA => Calculator::PHA(registers, bus),
+            Command::PHP => Calculator::PHP(registers, bus),
+            Command::PLA => Calculator::PLA(registers, bus),
+            Command::PLP => Calculator::PLP(registers, bus),
+            Command::CLC => Calculator::CLC(registers),
+            Command::CLD => Calculator::CLD(registers),
+            Command::CLI => Calculator::CLI(registers),
+            Command::CLV => Calculator::CLV(registers),
+            Command::SEC => Calculator::SEC(registers),
+            Command::SED => Calculator::SED(registers),
             Command::SEI => Calculator::SEI(registers),
-            Command::TXS => Calculator::TXS(registers),
+            Command::NOP if *mode == AddressingMode::Implied => {},
+            Command::NOP if *mode == AddressingMode::Immediate => {},
+            Command::NOP => bus.dummy_read(opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::LAX => Calculator::LAX(registers, bus, opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::SAX => Calculator::SAX(registers, bus, opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::DCP => Calculator::DCP(registers, bus, opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::ISC => Calculator::ISC(registers, bus, opeland, variant),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::SLO => Calculator::SLO(registers, bus, opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::RLA => Calculator::RLA(registers, bus, opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::SRE => Calculator::SRE(registers, bus, opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::RRA => Calculator::RRA(registers, bus, opeland, variant),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::ANC => Calculator::ANC(registers, opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::ALR => Calculator::ALR(registers, opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::ARR => Calculator::ARR(registers, opeland),
+            #[cfg(feature = "illegal-opcodes")]
+            Command::AXS => Calculator::AXS(registers, opeland),
+            Command::KIL => Calculator::KIL(registers, state, run_opecode),
         };
 
-        cycle
+        if branch_cycle > 0 {
+            let destination = registers.PC;
+            Calculator::read_on_branch(bus, next_pc, destination, branch_cycle);
+        }
+
+        let page_cross_cycle = (page_crossed && command.has_page_cross_penalty()) as usize;
+        let cycle = cycle + page_cross_cycle + branch_cycle;
+
+        Ok(cycle)
+    }
+
+    fn update_negative_and_zero(registers: &mut Registers, data: u8) {
+        registers.P.negative = (data & 0x80) == 0x80;
+        registers.P.zero = data == 0;
     }
 
     fn LDA<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
@@ -39,8 +160,7 @@ impl Calculator {
     fn LDA_immediate(registers: &mut Registers, opeland: u16) {
         let data = opeland as u8;
         registers.A = data;
-        registers.P.negative = (data & 0x80) == 0x80;
-        registers.P.zero = data == 0;
+        Calculator::update_negative_and_zero(registers, data);
     }
 
     fn LDX<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
@@ -50,8 +170,7 @@ impl Calculator {
     fn LDX_immediate(registers: &mut Registers, opeland: u16) {
         let data = opeland as u8;
         registers.X = data;
-        registers.P.negative = (data & 0x80) == 0x80;
-        registers.P.zero = data == 0;
+        Calculator::update_negative_and_zero(registers, data);
     }
 
     fn LDY<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
@@ -61,47 +180,487 @@ impl Calculator {
     fn LDY_immediate(registers: &mut Registers, opeland: u16) {
         let data = opeland as u8;
         registers.Y = data;
-        registers.P.negative = (data & 0x80) == 0x80;
-        registers.P.zero = data == 0;
+        Calculator::update_negative_and_zero(registers, data);
     }
 
     fn STA<T: CpuBus>(registers: &Registers, bus: &mut T, opeland: u16) {
         bus.write(opeland, registers.A);
     }
 
+    fn STX<T: CpuBus>(registers: &Registers, bus: &mut T, opeland: u16) {
+        bus.write(opeland, registers.X);
+    }
+
+    fn STY<T: CpuBus>(registers: &Registers, bus: &mut T, opeland: u16) {
+        bus.write(opeland, registers.Y);
+    }
+
+    fn TAX(registers: &mut Registers) {
+        let data = registers.A;
+        registers.X = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn TAY(registers: &mut Registers) {
+        let data = registers.A;
+        registers.Y = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn TSX(registers: &mut Registers) {
+        let data = registers.S;
+        registers.X = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn TXA(registers: &mut Registers) {
+        let data = registers.X;
+        registers.A = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
     fn TXS(registers: &mut Registers) {
         registers.S = registers.X;
     }
 
-    fn BNE(registers: &mut Registers, opeland: u16) {
-        if !registers.P.zero {
-            registers.PC = opeland;
+    fn TYA(registers: &mut Registers) {
+        let data = registers.Y;
+        registers.A = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn ADC<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16, variant: &Variant) {
+        Calculator::ADC_immediate(registers, bus.read(opeland) as u16, variant);
+    }
+
+    fn ADC_immediate(registers: &mut Registers, opeland: u16, variant: &Variant) {
+        let data = opeland as u8;
+
+        if registers.P.decimal && variant.has_decimal_mode() {
+            Calculator::add_decimal(registers, data);
+        } else {
+            Calculator::add_binary(registers, data);
         }
     }
 
-    fn DEY(registers: &mut Registers) {
-        let data = registers.Y - 1;
-        
-        registers.Y = data;
+    fn SBC<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16, variant: &Variant) {
+        Calculator::SBC_immediate(registers, bus.read(opeland) as u16, variant);
+    }
+
+    fn SBC_immediate(registers: &mut Registers, opeland: u16, variant: &Variant) {
+        let data = opeland as u8;
+
+        if registers.P.decimal && variant.has_decimal_mode() {
+            Calculator::subtract_decimal(registers, data);
+        } else {
+            // A - M - (1 - C) is the same as A + !M + C.
+            Calculator::add_binary(registers, !data);
+        }
+    }
+
+    fn add_binary(registers: &mut Registers, data: u8) {
+        let a = registers.A;
+        let sum = a as u16 + data as u16 + registers.P.carry as u16;
+        let result = sum as u8;
+
+        registers.A = result;
+        registers.P.carry = sum > 0xFF;
+        // overflow: both inputs have the same sign and the result has the other.
+        registers.P.overflow = (!(a ^ data) & (a ^ result) & 0x80) == 0x80;
+        Calculator::update_negative_and_zero(registers, result);
+    }
+
+    fn add_decimal(registers: &mut Registers, data: u8) {
+        /*
+            NMOS 6502 behaviour.
+            Z is taken from the binary sum, N and V from the sum before
+            the upper digit is adjusted, and C from the adjusted sum.
+
+            refer: http://www.6502.org/tutorials/decimal_mode.html
+        */
+        let a = registers.A;
+        let carry = registers.P.carry as u16;
+        let binary = a.wrapping_add(data).wrapping_add(carry as u8);
+
+        let mut lower = (a & 0x0F) as u16 + (data & 0x0F) as u16 + carry;
+        if lower >= 0x0A {
+            lower = ((lower + 0x06) & 0x0F) + 0x10;
+        }
+
+        let mut sum = (a & 0xF0) as u16 + (data & 0xF0) as u16 + lower;
+        let intermediate = sum as u8;
+        registers.P.negative = (intermediate & 0x80) == 0x80;
+        registers.P.overflow = (!(a ^ data) & (a ^ intermediate) & 0x80) == 0x80;
+        registers.P.zero = binary == 0;
+
+        if sum >= 0xA0 {
+            sum += 0x60;
+        }
+
+        registers.P.carry = sum > 0xFF;
+        registers.A = sum as u8;
+    }
+
+    fn subtract_decimal(registers: &mut Registers, data: u8) {
+        /*
+            NMOS 6502 behaviour.
+            All flags are the same as the binary subtraction, only A is adjusted.
+
+            refer: http://www.6502.org/tutorials/decimal_mode.html
+        */
+        let a = registers.A;
+        let borrow = !registers.P.carry as i16;
+
+        let mut lower = (a & 0x0F) as i16 - (data & 0x0F) as i16 - borrow;
+        if lower < 0 {
+            lower = ((lower - 0x06) & 0x0F) - 0x10;
+        }
+
+        let mut result = (a & 0xF0) as i16 - (data & 0xF0) as i16 + lower;
+        if result < 0 {
+            result -= 0x60;
+        }
+
+        Calculator::add_binary(registers, !data);
+        registers.A = result as u8;
+    }
+
+    fn AND<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        Calculator::AND_immediate(registers, bus.read(opeland) as u16);
+    }
+
+    fn AND_immediate(registers: &mut Registers, opeland: u16) {
+        let data = registers.A & opeland as u8;
+        registers.A = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn ORA<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        Calculator::ORA_immediate(registers, bus.read(opeland) as u16);
+    }
+
+    fn ORA_immediate(registers: &mut Registers, opeland: u16) {
+        let data = registers.A | opeland as u8;
+        registers.A = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn EOR<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        Calculator::EOR_immediate(registers, bus.read(opeland) as u16);
+    }
+
+    fn EOR_immediate(registers: &mut Registers, opeland: u16) {
+        let data = registers.A ^ opeland as u8;
+        registers.A = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn BIT<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        let data = bus.read(opeland);
         registers.P.negative = (data & 0x80) == 0x80;
-        registers.P.zero = data == 0;
+        registers.P.overflow = (data & 0x40) == 0x40;
+        registers.P.zero = (registers.A & data) == 0;
+    }
+
+    fn CMP<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        Calculator::CMP_immediate(registers, bus.read(opeland) as u16);
+    }
+
+    fn CMP_immediate(registers: &mut Registers, opeland: u16) {
+        let target = registers.A;
+        Calculator::compare(registers, target, opeland as u8);
+    }
+
+    fn CPX<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        Calculator::CPX_immediate(registers, bus.read(opeland) as u16);
+    }
+
+    fn CPX_immediate(registers: &mut Registers, opeland: u16) {
+        let target = registers.X;
+        Calculator::compare(registers, target, opeland as u8);
+    }
+
+    fn CPY<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        Calculator::CPY_immediate(registers, bus.read(opeland) as u16);
+    }
+
+    fn CPY_immediate(registers: &mut Registers, opeland: u16) {
+        let target = registers.Y;
+        Calculator::compare(registers, target, opeland as u8);
+    }
+
+    fn read_for_modify<T: CpuBus>(bus: &mut T, opeland: u16) -> u8 {
+        // read-modify-write instructions write the unmodified data back before the modified one.
+        let data = bus.read(opeland);
+        bus.dummy_write(opeland, data);
+        data
+    }
+
+    fn compare(registers: &mut Registers, target: u8, data: u8) {
+        registers.P.carry = target >= data;
+        Calculator::update_negative_and_zero(registers, target.wrapping_sub(data));
+    }
+
+    fn ASL<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        let data = Calculator::shift_left(registers, Calculator::read_for_modify(bus, opeland));
+        bus.write(opeland, data);
+    }
+
+    fn ASL_accumulator(registers: &mut Registers) {
+        let data = registers.A;
+        registers.A = Calculator::shift_left(registers, data);
+    }
+
+    fn shift_left(registers: &mut Registers, data: u8) -> u8 {
+        let shifted = data << 1;
+        registers.P.carry = (data & 0x80) == 0x80;
+        Calculator::update_negative_and_zero(registers, shifted);
+        shifted
+    }
+
+    fn LSR<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        let data = Calculator::shift_right(registers, Calculator::read_for_modify(bus, opeland));
+        bus.write(opeland, data);
+    }
+
+    fn LSR_accumulator(registers: &mut Registers) {
+        let data = registers.A;
+        registers.A = Calculator::shift_right(registers, data);
+    }
+
+    fn shift_right(registers: &mut Registers, data: u8) -> u8 {
+        let shifted = data >> 1;
+        registers.P.carry = (data & 0x01) == 0x01;
+        Calculator::update_negative_and_zero(registers, shifted);
+        shifted
+    }
+
+    fn ROL<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        let data = Calculator::rotate_left(registers, Calculator::read_for_modify(bus, opeland));
+        bus.write(opeland, data);
+    }
+
+    fn ROL_accumulator(registers: &mut Registers) {
+        let data = registers.A;
+        registers.A = Calculator::rotate_left(registers, data);
+    }
+
+    fn rotate_left(registers: &mut Registers, data: u8) -> u8 {
+        let rotated = data << 1 | registers.P.carry as u8;
+        registers.P.carry = (data & 0x80) == 0x80;
+        Calculator::update_negative_and_zero(registers, rotated);
+        rotated
+    }
+
+    fn ROR<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        let data = Calculator::rotate_right(registers, Calculator::read_for_modify(bus, opeland));
+        bus.write(opeland, data);
+    }
+
+    fn ROR_accumulator(registers: &mut Registers) {
+        let data = registers.A;
+        registers.A = Calculator::rotate_right(registers, data);
+    }
+
+    fn rotate_right(registers: &mut Registers, data: u8) -> u8 {
+        let rotated = data >> 1 | (registers.P.carry as u8) << 7;
+        registers.P.carry = (data & 0x01) == 0x01;
+        Calculator::update_negative_and_zero(registers, rotated);
+        rotated
+    }
+
+    fn INC<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        let data = Calculator::read_for_modify(bus, opeland).wrapping_add(1);
+        bus.write(opeland, data);
+        Calculator::update_negative_and_zero(registers, data);
     }
 
     fn INX(registers: &mut Registers) {
-        let data = registers.X + 1;
-        
+        let data = registers.X.wrapping_add(1);
+
         registers.X = data;
-        registers.P.negative = (data & 0x80) == 0x80;
-        registers.P.zero = data == 0;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn INY(registers: &mut Registers) {
+        let data = registers.Y.wrapping_add(1);
+
+        registers.Y = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn DEC<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        let data = Calculator::read_for_modify(bus, opeland).wrapping_sub(1);
+        bus.write(opeland, data);
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn DEX(registers: &mut Registers) {
+        let data = registers.X.wrapping_sub(1);
+
+        registers.X = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn DEY(registers: &mut Registers) {
+        let data = registers.Y.wrapping_sub(1);
+
+        registers.Y = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn BCC(registers: &mut Registers, opeland: u16) -> usize {
+        let condition = !registers.P.carry;
+        Calculator::branch(registers, opeland, condition)
+    }
+
+    fn BCS(registers: &mut Registers, opeland: u16) -> usize {
+        let condition = registers.P.carry;
+        Calculator::branch(registers, opeland, condition)
+    }
+
+    fn BEQ(registers: &mut Registers, opeland: u16) -> usize {
+        let condition = registers.P.zero;
+        Calculator::branch(registers, opeland, condition)
+    }
+
+    fn BMI(registers: &mut Registers, opeland: u16) -> usize {
+        let condition = registers.P.negative;
+        Calculator::branch(registers, opeland, condition)
+    }
+
+    fn BNE(registers: &mut Registers, opeland: u16) -> usize {
+        let condition = !registers.P.zero;
+        Calculator::branch(registers, opeland, condition)
+    }
+
+    fn BPL(registers: &mut Registers, opeland: u16) -> usize {
+        let condition = !registers.P.negative;
+        Calculator::branch(registers, opeland, condition)
+    }
+
+    fn BVC(registers: &mut Registers, opeland: u16) -> usize {
+        let condition = !registers.P.overflow;
+        Calculator::branch(registers, opeland, condition)
+    }
+
+    fn BVS(registers: &mut Registers, opeland: u16) -> usize {
+        let condition = registers.P.overflow;
+        Calculator::branch(registers, opeland, condition)
+    }
+
+    fn branch(registers: &mut Registers, opeland: u16, condition: bool) -> usize {
+        // a taken branch takes 1 more cycle, and 1 more when it jumps to another page.
+        if !condition {
+            return 0;
+        }
+
+        let page_crossed = (registers.PC & 0xFF00) != (opeland & 0xFF00);
+        registers.PC = opeland;
+        1 + page_crossed as usize
+    }
+
+    fn read_on_branch<T: CpuBus>(bus: &mut T, next_pc: u16, destination: u16, branch_cycle: usize) {
+        // a taken branch reads the next opecode, and the address before the page is fixed.
+        bus.dummy_read(next_pc);
+        if branch_cycle > 1 {
+            bus.dummy_read(next_pc & 0xFF00 | destination & 0x00FF);
+        }
     }
 
     fn JMP(registers: &mut Registers, opeland: u16) {
         registers.PC = opeland;
     }
 
+    fn JSR<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        // push the address of the last byte of JSR, RTS adds 1 to it.
+        let return_addr = registers.PC.wrapping_sub(1);
+        Controller::read_stack(registers, bus);
+        Controller::push_twice(registers, bus, return_addr);
+        registers.PC = opeland;
+    }
+
+    fn RTS<T: CpuBus>(registers: &mut Registers, bus: &mut T) {
+        Controller::read_stack(registers, bus);
+        let return_addr = Controller::pop_twice(registers, bus);
+        // the return address is read while PC is incremented.
+        bus.dummy_read(return_addr);
+        registers.PC = return_addr.wrapping_add(1);
+    }
+
+    fn BRK<T: CpuBus>(registers: &mut Registers, bus: &mut T) {
+        // BRK is 2 bytes instruction, the byte after the opecode is skipped.
+        registers.PC = registers.PC.wrapping_add(1);
+        Controller::interrupt(registers, bus, IRQ_VECTOR, true);
+    }
+
+    fn RTI<T: CpuBus>(registers: &mut Registers, bus: &mut T) {
+        Calculator::PLP(registers, bus);
+        registers.PC = Controller::pop_twice(registers, bus);
+    }
+
+    fn PHA<T: CpuBus>(registers: &mut Registers, bus: &mut T) {
+        let data = registers.A;
+        Controller::push(registers, bus, data);
+    }
+
+    fn PHP<T: CpuBus>(registers: &mut Registers, bus: &mut T) {
+        // the pushed status always has the B flag and the reserved bit set.
+        let data = registers.P.to_bit() | 0x30;
+        Controller::push(registers, bus, data);
+    }
+
+    fn PLA<T: CpuBus>(registers: &mut Registers, bus: &mut T) {
+        Controller::read_stack(registers, bus);
+        let data = Controller::pop(registers, bus);
+        registers.A = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    fn PLP<T: CpuBus>(registers: &mut Registers, bus: &mut T) {
+        // B flag and reserved bit don't exist in the register, they are ignored.
+        Controller::read_stack(registers, bus);
+        let data = Controller::pop(registers, bus);
+        let break_mode = registers.P.break_mode;
+
+        registers.P.set_by_bit(data);
+        registers.P.break_mode = break_mode;
+        registers.P.reserved = true;
+    }
+
+    fn CLC(registers: &mut Registers) {
+        registers.P.carry = false;
+    }
+
+    fn CLD(registers: &mut Registers) {
+        registers.P.decimal = false;
+    }
+
+    fn CLI(registers: &mut Registers) {
+        registers.P.interrupt = false;
+    }
+
+    fn CLV(registers: &mut Registers) {
+        registers.P.overflow = false;
+    }
+
+    fn SEC(registers: &mut Registers) {
+        registers.P.carry = true;
+    }
+
+    fn SED(registers: &mut Registers) {
+        registers.P.decimal = true;
+    }
+
     fn SEI(registers: &mut Registers) {
         registers.P.interrupt = true;
     }
+
+    fn KIL(registers: &mut Registers, state: &mut CpuState, opecode: u8) {
+        // the CPU stops with PC pointing the jammed opecode.
+        registers.PC = registers.PC.wrapping_sub(1);
+        *state = CpuState::Jammed { pc: registers.PC, opecode: opecode };
+    }
 }
 
 #[cfg(test)]
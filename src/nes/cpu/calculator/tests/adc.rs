@@ -0,0 +1,104 @@
+use super::*;
+
+#[test]
+fn ADC_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0x10;
+    registers.P.carry = true;
+    bus.write(addr, 0x20);
+
+    Calculator::ADC(&mut registers, &mut bus, addr, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0x31);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.overflow, false);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn ADC_immediate_update_carry_and_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0xFF;
+    registers.P.carry = false;
+
+    Calculator::ADC_immediate(&mut registers, 0x01, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.overflow, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn ADC_immediate_update_overflow_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x7F;
+    registers.P.carry = false;
+
+    // positive + positive = negative
+    Calculator::ADC_immediate(&mut registers, 0x01, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0x80);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.overflow, true);
+    assert_eq!(registers.P.negative, true);
+
+    let mut registers = Registers::new();
+    registers.A = 0x80;
+    registers.P.carry = false;
+
+    // negative + negative = positive
+    Calculator::ADC_immediate(&mut registers, 0xFF, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0x7F);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.overflow, true);
+    assert_eq!(registers.P.negative, false);
+}
+
+#[test]
+fn ADC_immediate_ignore_decimal_on_ricoh_2a03_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x09;
+    registers.P.carry = false;
+    registers.P.decimal = true;
+
+    Calculator::ADC_immediate(&mut registers, 0x01, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0x0A);
+}
+
+#[test]
+fn ADC_immediate_decimal_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x09;
+    registers.P.carry = false;
+    registers.P.decimal = true;
+
+    Calculator::ADC_immediate(&mut registers, 0x01, &Variant::Mos6502);
+    assert_eq!(registers.A, 0x10);
+    assert_eq!(registers.P.carry, false);
+
+    let mut registers = Registers::new();
+    registers.A = 0x58;
+    registers.P.carry = true;
+    registers.P.decimal = true;
+
+    // 58 + 46 + 1 = 105
+    Calculator::ADC_immediate(&mut registers, 0x46, &Variant::Mos6502);
+    assert_eq!(registers.A, 0x05);
+    assert_eq!(registers.P.carry, true);
+}
+
+#[test]
+fn ADC_immediate_decimal_zero_flag_from_binary_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x99;
+    registers.P.carry = false;
+    registers.P.decimal = true;
+
+    // 99 + 01 = 00 with carry, but the binary sum 0x9A is not zero.
+    Calculator::ADC_immediate(&mut registers, 0x01, &Variant::Mos6502);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.zero, false);
+}
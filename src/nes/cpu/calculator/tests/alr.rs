@@ -0,0 +1,13 @@
+use super::*;
+
+#[test]
+fn ALR_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b11110011;
+
+    Calculator::ALR(&mut registers, 0b00001111);
+    assert_eq!(registers.A, 0b00000001);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn ANC_test() {
+    let mut registers = Registers::new();
+    registers.A = 0xF0;
+    registers.P.carry = false;
+
+    Calculator::ANC(&mut registers, 0x80);
+    assert_eq!(registers.A, 0x80);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.carry, true);
+
+    Calculator::ANC(&mut registers, 0x00);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.zero, true);
+    assert_eq!(registers.P.carry, false);
+}
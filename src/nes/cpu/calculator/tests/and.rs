@@ -0,0 +1,35 @@
+use super::*;
+
+#[test]
+fn AND_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0b11001100;
+    bus.write(addr, 0b10101010);
+
+    Calculator::AND(&mut registers, &mut bus, addr);
+    assert_eq!(registers.A, 0b10001000);
+}
+
+#[test]
+fn AND_immediate_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b00001111;
+
+    Calculator::AND_immediate(&mut registers, 0b11110000);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn AND_immediate_update_negative_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x80;
+
+    Calculator::AND_immediate(&mut registers, 0xFF);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,24 @@
+use super::*;
+
+#[test]
+fn ARR_test() {
+    let mut registers = Registers::new();
+    registers.A = 0xFF;
+    registers.P.carry = true;
+
+    // AND: 0xC0, ROR: 0xE0 -> bit 6: 1, bit 5: 1
+    Calculator::ARR(&mut registers, 0xC0);
+    assert_eq!(registers.A, 0xE0);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.overflow, false);
+    assert_eq!(registers.P.negative, true);
+
+    // AND: 0x80, ROR: 0x40 -> bit 6: 1, bit 5: 0
+    registers.A = 0xFF;
+    registers.P.carry = false;
+    Calculator::ARR(&mut registers, 0x80);
+    assert_eq!(registers.A, 0x40);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.overflow, true);
+    assert_eq!(registers.P.negative, false);
+}
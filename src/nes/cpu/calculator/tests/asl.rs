@@ -0,0 +1,39 @@
+use super::*;
+
+#[test]
+fn ASL_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    bus.write(addr, 0b10100001);
+
+    Calculator::ASL(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0b01000010);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn ASL_accumulator_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b01000001;
+
+    Calculator::ASL_accumulator(&mut registers);
+    assert_eq!(registers.A, 0b10000010);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn ASL_accumulator_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b10000000;
+
+    Calculator::ASL_accumulator(&mut registers);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.zero, true);
+}
@@ -0,0 +1,27 @@
+use super::*;
+
+#[test]
+fn AXS_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b11110000;
+    registers.X = 0b00111100;
+
+    // (A & X) = 0x30, 0x30 - 0x10
+    Calculator::AXS(&mut registers, 0x10);
+    assert_eq!(registers.X, 0x20);
+    assert_eq!(registers.A, 0b11110000);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn AXS_borrow_test() {
+    let mut registers = Registers::new();
+    registers.A = 0xFF;
+    registers.X = 0x01;
+
+    Calculator::AXS(&mut registers, 0x02);
+    assert_eq!(registers.X, 0xFF);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.negative, true);
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn BCC_test() {
+    let mut registers = Registers::new();
+    registers.P.carry = false;
+
+    let opeland = 0x0090;
+    Calculator::BCC(&mut registers, opeland);
+
+    assert_eq!(opeland, registers.PC);
+}
+
+#[test]
+fn BCC_not_branch_test() {
+    let mut registers = Registers::new();
+    registers.P.carry = true;
+    registers.PC = 0x8000;
+
+    Calculator::BCC(&mut registers, 0x0090);
+
+    assert_eq!(registers.PC, 0x8000);
+}
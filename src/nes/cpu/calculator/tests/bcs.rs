@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn BCS_test() {
+    let mut registers = Registers::new();
+    registers.P.carry = true;
+
+    let opeland = 0x0090;
+    Calculator::BCS(&mut registers, opeland);
+
+    assert_eq!(opeland, registers.PC);
+}
+
+#[test]
+fn BCS_not_branch_test() {
+    let mut registers = Registers::new();
+    registers.P.carry = false;
+    registers.PC = 0x8000;
+
+    Calculator::BCS(&mut registers, 0x0090);
+
+    assert_eq!(registers.PC, 0x8000);
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn BEQ_test() {
+    let mut registers = Registers::new();
+    registers.P.zero = true;
+
+    let opeland = 0x0090;
+    Calculator::BEQ(&mut registers, opeland);
+
+    assert_eq!(opeland, registers.PC);
+}
+
+#[test]
+fn BEQ_not_branch_test() {
+    let mut registers = Registers::new();
+    registers.P.zero = false;
+    registers.PC = 0x8000;
+
+    Calculator::BEQ(&mut registers, 0x0090);
+
+    assert_eq!(registers.PC, 0x8000);
+}
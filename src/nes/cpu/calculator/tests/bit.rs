@@ -0,0 +1,34 @@
+use super::*;
+
+#[test]
+fn BIT_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0b00000001;
+    bus.write(addr, 0b11000001);
+
+    Calculator::BIT(&mut registers, &mut bus, addr);
+    assert_eq!(registers.A, 0b00000001); // A is not changed
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.overflow, true);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn BIT_update_zero_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0b00000001;
+    registers.P.negative = true;
+    registers.P.overflow = true;
+    bus.write(addr, 0b00000010);
+
+    Calculator::BIT(&mut registers, &mut bus, addr);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.overflow, false);
+    assert_eq!(registers.P.zero, true);
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn BMI_test() {
+    let mut registers = Registers::new();
+    registers.P.negative = true;
+
+    let opeland = 0x0090;
+    Calculator::BMI(&mut registers, opeland);
+
+    assert_eq!(opeland, registers.PC);
+}
+
+#[test]
+fn BMI_not_branch_test() {
+    let mut registers = Registers::new();
+    registers.P.negative = false;
+    registers.PC = 0x8000;
+
+    Calculator::BMI(&mut registers, 0x0090);
+
+    assert_eq!(registers.PC, 0x8000);
+}
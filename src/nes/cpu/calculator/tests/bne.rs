@@ -9,4 +9,23 @@ fn BNE_test() {
     Calculator::BNE(&mut registers, opeland);
 
     assert_eq!(opeland, registers.PC);
-}
\ No newline at end of file
+}
+#[test]
+fn BNE_cycle_test() {
+    let mut registers = Registers::new();
+
+    // not taken
+    registers.P.zero = true;
+    registers.PC = 0x8010;
+    assert_eq!(Calculator::BNE(&mut registers, 0x8020), 0);
+
+    // taken in the same page
+    registers.P.zero = false;
+    registers.PC = 0x8010;
+    assert_eq!(Calculator::BNE(&mut registers, 0x8020), 1);
+
+    // taken to another page
+    registers.PC = 0x8010;
+    assert_eq!(Calculator::BNE(&mut registers, 0x7FF0), 2);
+    assert_eq!(registers.PC, 0x7FF0);
+}
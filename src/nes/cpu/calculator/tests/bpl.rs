@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn BPL_test() {
+    let mut registers = Registers::new();
+    registers.P.negative = false;
+
+    let opeland = 0x0090;
+    Calculator::BPL(&mut registers, opeland);
+
+    assert_eq!(opeland, registers.PC);
+}
+
+#[test]
+fn BPL_not_branch_test() {
+    let mut registers = Registers::new();
+    registers.P.negative = true;
+    registers.PC = 0x8000;
+
+    Calculator::BPL(&mut registers, 0x0090);
+
+    assert_eq!(registers.PC, 0x8000);
+}
@@ -0,0 +1,19 @@
+use super::*;
+
+#[test]
+fn BRK_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    // BRK is placed at 0x0010, PC points the padding byte.
+    registers.PC = 0x0011;
+    registers.S = 0xFD;
+    registers.P.interrupt = false;
+
+    Calculator::BRK(&mut registers, &mut bus);
+    assert_eq!(registers.S, 0xFA);
+    assert_eq!(registers.P.interrupt, true);
+    assert_eq!(bus.read(0x01FD), 0x00);
+    assert_eq!(bus.read(0x01FC), 0x12);
+    assert_eq!(bus.read(0x01FB) & 0x30, 0x30); // B flag and reserved bit
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn BVC_test() {
+    let mut registers = Registers::new();
+    registers.P.overflow = false;
+
+    let opeland = 0x0090;
+    Calculator::BVC(&mut registers, opeland);
+
+    assert_eq!(opeland, registers.PC);
+}
+
+#[test]
+fn BVC_not_branch_test() {
+    let mut registers = Registers::new();
+    registers.P.overflow = true;
+    registers.PC = 0x8000;
+
+    Calculator::BVC(&mut registers, 0x0090);
+
+    assert_eq!(registers.PC, 0x8000);
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn BVS_test() {
+    let mut registers = Registers::new();
+    registers.P.overflow = true;
+
+    let opeland = 0x0090;
+    Calculator::BVS(&mut registers, opeland);
+
+    assert_eq!(opeland, registers.PC);
+}
+
+#[test]
+fn BVS_not_branch_test() {
+    let mut registers = Registers::new();
+    registers.P.overflow = false;
+    registers.PC = 0x8000;
+
+    Calculator::BVS(&mut registers, 0x0090);
+
+    assert_eq!(registers.PC, 0x8000);
+}
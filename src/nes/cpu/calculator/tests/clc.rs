@@ -0,0 +1,10 @@
+use super::*;
+
+#[test]
+fn CLC_test() {
+    let mut registers = Registers::new();
+    registers.P.carry = true;
+
+    Calculator::CLC(&mut registers);
+    assert_eq!(registers.P.carry, false);
+}
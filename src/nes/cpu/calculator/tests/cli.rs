@@ -0,0 +1,10 @@
+use super::*;
+
+#[test]
+fn CLI_test() {
+    let mut registers = Registers::new();
+    registers.P.interrupt = true;
+
+    Calculator::CLI(&mut registers);
+    assert_eq!(registers.P.interrupt, false);
+}
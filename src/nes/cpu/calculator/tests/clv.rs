@@ -0,0 +1,10 @@
+use super::*;
+
+#[test]
+fn CLV_test() {
+    let mut registers = Registers::new();
+    registers.P.overflow = true;
+
+    Calculator::CLV(&mut registers);
+    assert_eq!(registers.P.overflow, false);
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn CMP_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0x20;
+    bus.write(addr, 0x10);
+
+    Calculator::CMP(&mut registers, &mut bus, addr);
+    assert_eq!(registers.A, 0x20); // register is not changed
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn CMP_immediate_equal_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x20;
+
+    Calculator::CMP_immediate(&mut registers, 0x20);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn CMP_immediate_less_than_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x10;
+
+    // 0x10 - 0x20 = 0xF0
+    Calculator::CMP_immediate(&mut registers, 0x20);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn CPX_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.X = 0x20;
+    bus.write(addr, 0x10);
+
+    Calculator::CPX(&mut registers, &mut bus, addr);
+    assert_eq!(registers.X, 0x20); // register is not changed
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn CPX_immediate_equal_test() {
+    let mut registers = Registers::new();
+    registers.X = 0x20;
+
+    Calculator::CPX_immediate(&mut registers, 0x20);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn CPX_immediate_less_than_test() {
+    let mut registers = Registers::new();
+    registers.X = 0x10;
+
+    // 0x10 - 0x20 = 0xF0
+    Calculator::CPX_immediate(&mut registers, 0x20);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn CPY_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.Y = 0x20;
+    bus.write(addr, 0x10);
+
+    Calculator::CPY(&mut registers, &mut bus, addr);
+    assert_eq!(registers.Y, 0x20); // register is not changed
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn CPY_immediate_equal_test() {
+    let mut registers = Registers::new();
+    registers.Y = 0x20;
+
+    Calculator::CPY_immediate(&mut registers, 0x20);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn CPY_immediate_less_than_test() {
+    let mut registers = Registers::new();
+    registers.Y = 0x10;
+
+    // 0x10 - 0x20 = 0xF0
+    Calculator::CPY_immediate(&mut registers, 0x20);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn DCP_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0x20;
+    bus.write(addr, 0x21);
+
+    Calculator::DCP(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0x20);
+    assert_eq!(registers.A, 0x20);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.zero, true);
+    assert_eq!(registers.P.negative, false);
+}
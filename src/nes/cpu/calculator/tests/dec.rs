@@ -0,0 +1,29 @@
+use super::*;
+
+#[test]
+fn DEC_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    bus.write(addr, 0x01);
+
+    Calculator::DEC(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn DEC_wrap_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    bus.write(addr, 0x00);
+
+    Calculator::DEC(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0xFF);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn DEX_test() {
+    let mut registers = Registers::new();
+    registers.X = 0x21;
+
+    Calculator::DEX(&mut registers);
+    assert_eq!(registers.X, 0x20);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn DEX_wrap_test() {
+    let mut registers = Registers::new();
+    registers.X = 0x00;
+
+    Calculator::DEX(&mut registers);
+    assert_eq!(registers.X, 0xFF);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -34,4 +34,15 @@ fn DEY_update_negative_test() {
     assert_eq!(registers.Y, opeland);
     assert_eq!(registers.P.negative, true);
     assert_eq!(registers.P.zero, false);
-}
\ No newline at end of file
+}
+
+#[test]
+fn DEY_wrap_test() {
+    let mut registers = Registers::new();
+    registers.Y = 0x00;
+
+    Calculator::DEY(&mut registers);
+    assert_eq!(registers.Y, 0xFF);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,35 @@
+use super::*;
+
+#[test]
+fn EOR_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0b01001100;
+    bus.write(addr, 0b00101010);
+
+    Calculator::EOR(&mut registers, &mut bus, addr);
+    assert_eq!(registers.A, 0b01100110);
+}
+
+#[test]
+fn EOR_immediate_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b10101010;
+
+    Calculator::EOR_immediate(&mut registers, 0b10101010);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn EOR_immediate_update_negative_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x80;
+
+    Calculator::EOR_immediate(&mut registers, 0x01);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
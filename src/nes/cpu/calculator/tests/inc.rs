@@ -0,0 +1,29 @@
+use super::*;
+
+#[test]
+fn INC_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    bus.write(addr, 0x7F);
+
+    Calculator::INC(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0x80);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn INC_wrap_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    bus.write(addr, 0xFF);
+
+    Calculator::INC(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
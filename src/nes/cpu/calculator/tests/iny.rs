@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn INY_test() {
+    let mut registers = Registers::new();
+    registers.Y = 0x1F;
+
+    Calculator::INY(&mut registers);
+    assert_eq!(registers.Y, 0x20);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn INY_wrap_test() {
+    let mut registers = Registers::new();
+    registers.Y = 0xFF;
+
+    Calculator::INY(&mut registers);
+    assert_eq!(registers.Y, 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
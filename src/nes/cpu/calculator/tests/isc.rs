@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn ISC_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0x30;
+    registers.P.carry = true;
+    bus.write(addr, 0x0F);
+
+    Calculator::ISC(&mut registers, &mut bus, addr, &Variant::Ricoh2A03);
+    assert_eq!(bus.read(addr), 0x10);
+    assert_eq!(registers.A, 0x20);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,17 @@
+use super::*;
+
+#[test]
+fn JSR_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    // JSR $0090 is placed at 0x0010-0x0012, PC points the next instruction.
+    registers.PC = 0x0013;
+    registers.S = 0xFD;
+
+    Calculator::JSR(&mut registers, &mut bus, 0x0090);
+    assert_eq!(registers.PC, 0x0090);
+    assert_eq!(registers.S, 0xFB);
+    assert_eq!(bus.read(0x01FD), 0x00); // upper address of 0x0012
+    assert_eq!(bus.read(0x01FC), 0x12); // lower address of 0x0012
+}
@@ -0,0 +1,14 @@
+use super::*;
+
+#[test]
+fn KIL_test() {
+    let mut registers = Registers::new();
+    let mut state = CpuState::Running;
+
+    // KIL is placed at 0x0010, PC points the next byte.
+    registers.PC = 0x0011;
+
+    Calculator::KIL(&mut registers, &mut state, 0x02);
+    assert_eq!(registers.PC, 0x0010);
+    assert_eq!(state, CpuState::Jammed { pc: 0x0010, opecode: 0x02 });
+}
@@ -0,0 +1,16 @@
+use super::*;
+
+#[test]
+fn LAX_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    bus.write(addr, 0x90);
+
+    Calculator::LAX(&mut registers, &mut bus, addr);
+    assert_eq!(registers.A, 0x90);
+    assert_eq!(registers.X, 0x90);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
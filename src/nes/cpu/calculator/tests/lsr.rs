@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn LSR_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    bus.write(addr, 0b10100001);
+
+    Calculator::LSR(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0b01010000);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn LSR_accumulator_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b10000010;
+    registers.P.negative = true;
+
+    Calculator::LSR_accumulator(&mut registers);
+    assert_eq!(registers.A, 0b01000001);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.negative, false); // bit 7 is always cleared
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn LSR_accumulator_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b00000001;
+
+    Calculator::LSR_accumulator(&mut registers);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.zero, true);
+}
@@ -2,6 +2,8 @@ use super::*;
 
 use nes::cpu::bus::CpuBus;
 use nes::cpu::registers::Registers;
+use nes::cpu::variant::Variant;
+use nes::cpu::state::CpuState;
 
 struct BusMock {
     ram: Vec<u8>,
@@ -9,7 +11,7 @@ struct BusMock {
 
 impl BusMock {
     fn new() -> Self {
-        Self { ram: vec![0; 0x100] }
+        Self { ram: vec![0; 0x10000] }
     }
 }
 
@@ -29,13 +31,84 @@ impl CpuBus for BusMock {
     }
 }
 
+mod adc;
+mod and;
+mod asl;
+mod bcc;
+mod bcs;
+mod beq;
+mod bit;
+mod bmi;
 mod bne;
+mod bpl;
+mod brk;
+mod bvc;
+mod bvs;
+mod clc;
+mod cld;
+mod cli;
+mod clv;
+mod cmp;
+mod cpx;
+mod cpy;
+mod dec;
+mod dex;
 mod dey;
+mod eor;
+mod inc;
 mod inx;
+mod iny;
 mod jmp;
+mod jsr;
+mod kil;
 mod lda;
 mod ldx;
 mod ldy;
+mod lsr;
+mod ora;
+mod pha;
+mod php;
+mod pla;
+mod plp;
+mod rol;
+mod ror;
+mod rti;
+mod rts;
+mod sbc;
+mod sec;
+mod sed;
 mod sei;
 mod sta;
-mod txs;
\ No newline at end of file
+mod stx;
+mod sty;
+mod tax;
+mod tay;
+mod tsx;
+mod txa;
+mod txs;
+mod tya;
+
+#[cfg(feature = "illegal-opcodes")]
+mod alr;
+#[cfg(feature = "illegal-opcodes")]
+mod anc;
+#[cfg(feature = "illegal-opcodes")]
+mod arr;
+#[cfg(feature = "illegal-opcodes")]
+mod axs;
+#[cfg(feature = "illegal-opcodes")]
+mod dcp;
+#[cfg(feature = "illegal-opcodes")]
+mod isc;
+#[cfg(feature = "illegal-opcodes")]
+mod lax;
+#[cfg(feature = "illegal-opcodes")]
+mod rla;
+#[cfg(feature = "illegal-opcodes")]
+mod rra;
+#[cfg(feature = "illegal-opcodes")]
+mod sax;
+#[cfg(feature = "illegal-opcodes")]
+mod slo;
+#[cfg(feature = "illegal-opcodes")]
+mod sre;
\ No newline at end of file
@@ -0,0 +1,35 @@
+use super::*;
+
+#[test]
+fn ORA_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0b01001100;
+    bus.write(addr, 0b00101010);
+
+    Calculator::ORA(&mut registers, &mut bus, addr);
+    assert_eq!(registers.A, 0b01101110);
+}
+
+#[test]
+fn ORA_immediate_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x00;
+
+    Calculator::ORA_immediate(&mut registers, 0x00);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn ORA_immediate_update_negative_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x80;
+
+    Calculator::ORA_immediate(&mut registers, 0x01);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,14 @@
+use super::*;
+
+#[test]
+fn PHA_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.A = 0x89;
+    registers.S = 0xFD;
+
+    Calculator::PHA(&mut registers, &mut bus);
+    assert_eq!(bus.read(0x01FD), 0x89);
+    assert_eq!(registers.S, 0xFC);
+}
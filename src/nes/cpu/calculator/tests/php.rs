@@ -0,0 +1,16 @@
+use super::*;
+
+#[test]
+fn PHP_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.S = 0xFD;
+    registers.P.set_by_bit(0b11000011);
+
+    // B flag and reserved bit are set in the pushed value.
+    Calculator::PHP(&mut registers, &mut bus);
+    assert_eq!(bus.read(0x01FD), 0b11110011);
+    assert_eq!(registers.S, 0xFC);
+    assert_eq!(registers.P.to_bit(), 0b11000011);
+}
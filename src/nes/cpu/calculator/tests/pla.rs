@@ -0,0 +1,45 @@
+use super::*;
+
+#[test]
+fn PLA_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.S = 0xFC;
+    bus.write(0x01FD, 0x20);
+
+    Calculator::PLA(&mut registers, &mut bus);
+    assert_eq!(registers.A, 0x20);
+    assert_eq!(registers.S, 0xFD);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn PLA_update_zero_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.A = 0x20;
+    registers.S = 0xFC;
+    bus.write(0x01FD, 0x00);
+
+    Calculator::PLA(&mut registers, &mut bus);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn PLA_update_negative_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.S = 0xFC;
+    bus.write(0x01FD, 0x90);
+
+    Calculator::PLA(&mut registers, &mut bus);
+    assert_eq!(registers.A, 0x90);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
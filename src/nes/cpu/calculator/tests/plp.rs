@@ -0,0 +1,34 @@
+use super::*;
+
+#[test]
+fn PLP_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.S = 0xFC;
+    bus.write(0x01FD, 0b11001011);
+
+    Calculator::PLP(&mut registers, &mut bus);
+    assert_eq!(registers.S, 0xFD);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.overflow, true);
+    assert_eq!(registers.P.decimal, true);
+    assert_eq!(registers.P.interrupt, false);
+    assert_eq!(registers.P.zero, true);
+    assert_eq!(registers.P.carry, true);
+}
+
+#[test]
+fn PLP_ignore_break_and_reserved_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.S = 0xFC;
+    registers.P.break_mode = true;
+    registers.P.reserved = true;
+    bus.write(0x01FD, 0b00000000);
+
+    Calculator::PLP(&mut registers, &mut bus);
+    assert_eq!(registers.P.break_mode, true);
+    assert_eq!(registers.P.reserved, true);
+}
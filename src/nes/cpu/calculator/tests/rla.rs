@@ -0,0 +1,19 @@
+use super::*;
+
+#[test]
+fn RLA_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0b11110000;
+    registers.P.carry = true;
+    bus.write(addr, 0b10100000);
+
+    Calculator::RLA(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0b01000001);
+    assert_eq!(registers.A, 0b01000000);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
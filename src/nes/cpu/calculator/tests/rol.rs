@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn ROL_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.P.carry = true;
+    bus.write(addr, 0b10100000);
+
+    // carry is shifted into bit 0, bit 7 is shifted into carry.
+    Calculator::ROL(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0b01000001);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn ROL_accumulator_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b01000001;
+    registers.P.carry = false;
+
+    Calculator::ROL_accumulator(&mut registers);
+    assert_eq!(registers.A, 0b10000010);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn ROL_accumulator_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b10000000;
+    registers.P.carry = false;
+
+    Calculator::ROL_accumulator(&mut registers);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.zero, true);
+}
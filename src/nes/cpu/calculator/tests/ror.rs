@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn ROR_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.P.carry = true;
+    bus.write(addr, 0b00000101);
+
+    // carry is shifted into bit 7, bit 0 is shifted into carry.
+    Calculator::ROR(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0b10000010);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn ROR_accumulator_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b10000010;
+    registers.P.carry = false;
+
+    Calculator::ROR_accumulator(&mut registers);
+    assert_eq!(registers.A, 0b01000001);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn ROR_accumulator_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0b00000001;
+    registers.P.carry = false;
+
+    Calculator::ROR_accumulator(&mut registers);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.zero, true);
+}
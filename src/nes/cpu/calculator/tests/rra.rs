@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn RRA_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0x10;
+    registers.P.carry = false;
+    bus.write(addr, 0x03);
+
+    // ROR: 0x03 -> 0x01 with carry, ADC: 0x10 + 0x01 + carry
+    Calculator::RRA(&mut registers, &mut bus, addr, &Variant::Ricoh2A03);
+    assert_eq!(bus.read(addr), 0x01);
+    assert_eq!(registers.A, 0x12);
+    assert_eq!(registers.P.carry, false);
+}
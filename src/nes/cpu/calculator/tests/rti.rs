@@ -0,0 +1,22 @@
+use super::*;
+
+#[test]
+fn RTI_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.S = 0xFA;
+    bus.write(0x01FB, 0b11000011); // status
+    bus.write(0x01FC, 0x12); // lower address
+    bus.write(0x01FD, 0x00); // upper address
+
+    // RTI doesn't add 1 to the return address unlike RTS.
+    Calculator::RTI(&mut registers, &mut bus);
+    assert_eq!(registers.PC, 0x0012);
+    assert_eq!(registers.S, 0xFD);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.overflow, true);
+    assert_eq!(registers.P.zero, true);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.interrupt, false);
+}
@@ -0,0 +1,28 @@
+use super::*;
+
+#[test]
+fn RTS_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.S = 0xFB;
+    bus.write(0x01FD, 0x00); // upper address
+    bus.write(0x01FC, 0x12); // lower address
+
+    Calculator::RTS(&mut registers, &mut bus);
+    assert_eq!(registers.PC, 0x0013);
+    assert_eq!(registers.S, 0xFD);
+}
+
+#[test]
+fn JSR_and_RTS_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    registers.PC = 0x0013;
+    Calculator::JSR(&mut registers, &mut bus, 0x0090);
+    Calculator::RTS(&mut registers, &mut bus);
+
+    assert_eq!(registers.PC, 0x0013);
+    assert_eq!(registers.S, Registers::new().S);
+}
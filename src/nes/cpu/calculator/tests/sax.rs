@@ -0,0 +1,16 @@
+use super::*;
+
+#[test]
+fn SAX_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0b11001100;
+    registers.X = 0b10101010;
+    registers.P.zero = false;
+
+    Calculator::SAX(&registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0b10001000);
+    assert_eq!(registers.P.zero, false); // flags are not changed
+}
@@ -0,0 +1,90 @@
+use super::*;
+
+#[test]
+fn SBC_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0x30;
+    registers.P.carry = true; // no borrow
+    bus.write(addr, 0x10);
+
+    Calculator::SBC(&mut registers, &mut bus, addr, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0x20);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.overflow, false);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn SBC_immediate_borrow_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x10;
+    registers.P.carry = false; // borrow
+
+    Calculator::SBC_immediate(&mut registers, 0x10, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0xFF);
+    assert_eq!(registers.P.carry, false);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn SBC_immediate_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x10;
+    registers.P.carry = true;
+
+    Calculator::SBC_immediate(&mut registers, 0x10, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn SBC_immediate_update_overflow_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x80;
+    registers.P.carry = true;
+
+    // negative - positive = positive
+    Calculator::SBC_immediate(&mut registers, 0x01, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0x7F);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.overflow, true);
+}
+
+#[test]
+fn SBC_immediate_decimal_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x10;
+    registers.P.carry = true;
+    registers.P.decimal = true;
+
+    Calculator::SBC_immediate(&mut registers, 0x01, &Variant::Mos6502);
+    assert_eq!(registers.A, 0x09);
+    assert_eq!(registers.P.carry, true);
+
+    let mut registers = Registers::new();
+    registers.A = 0x12;
+    registers.P.carry = true;
+    registers.P.decimal = true;
+
+    // 12 - 21 = 91 with borrow
+    Calculator::SBC_immediate(&mut registers, 0x21, &Variant::Mos6502);
+    assert_eq!(registers.A, 0x91);
+    assert_eq!(registers.P.carry, false);
+}
+
+#[test]
+fn SBC_immediate_ignore_decimal_on_ricoh_2a03_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x10;
+    registers.P.carry = true;
+    registers.P.decimal = true;
+
+    Calculator::SBC_immediate(&mut registers, 0x01, &Variant::Ricoh2A03);
+    assert_eq!(registers.A, 0x0F);
+}
@@ -0,0 +1,10 @@
+use super::*;
+
+#[test]
+fn SEC_test() {
+    let mut registers = Registers::new();
+    registers.P.carry = false;
+
+    Calculator::SEC(&mut registers);
+    assert_eq!(registers.P.carry, true);
+}
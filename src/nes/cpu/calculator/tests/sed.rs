@@ -0,0 +1,10 @@
+use super::*;
+
+#[test]
+fn SED_test() {
+    let mut registers = Registers::new();
+    registers.P.decimal = false;
+
+    Calculator::SED(&mut registers);
+    assert_eq!(registers.P.decimal, true);
+}
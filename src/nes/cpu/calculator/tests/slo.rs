@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn SLO_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0b00000001;
+    bus.write(addr, 0b10100000);
+
+    Calculator::SLO(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0b01000000);
+    assert_eq!(registers.A, 0b01000001);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn SRE_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+
+    let addr = 0x0010;
+    registers.A = 0b11110000;
+    bus.write(addr, 0b00000011);
+
+    Calculator::SRE(&mut registers, &mut bus, addr);
+    assert_eq!(bus.read(addr), 0b00000001);
+    assert_eq!(registers.A, 0b11110001);
+    assert_eq!(registers.P.carry, true);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
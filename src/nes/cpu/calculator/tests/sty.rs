@@ -0,0 +1,14 @@
+use super::*;
+
+#[test]
+fn STY_test() {
+    let mut registers = Registers::new();
+    let mut bus = BusMock::new();
+    let opeland = 0x90;
+
+    registers.Y = 0x89;
+    Calculator::STY(&registers, &mut bus, opeland);
+
+    let actual = bus.read(opeland as u16);
+    assert_eq!(actual, registers.Y);
+}
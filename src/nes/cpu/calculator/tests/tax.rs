@@ -0,0 +1,36 @@
+use super::*;
+
+#[test]
+fn TAX_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x20;
+    registers.X = 0x00;
+
+    Calculator::TAX(&mut registers);
+    assert_eq!(registers.X, 0x20);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn TAX_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x00;
+    registers.X = 0x20;
+
+    Calculator::TAX(&mut registers);
+    assert_eq!(registers.X, 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn TAX_update_negative_test() {
+    let mut registers = Registers::new();
+    registers.A = 0x90; // Nagative value(over than 0x80)
+
+    Calculator::TAX(&mut registers);
+    assert_eq!(registers.X, 0x90);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
@@ -0,0 +1,36 @@
+use super::*;
+
+#[test]
+fn TXA_test() {
+    let mut registers = Registers::new();
+    registers.X = 0x20;
+    registers.A = 0x00;
+
+    Calculator::TXA(&mut registers);
+    assert_eq!(registers.A, 0x20);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn TXA_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.X = 0x00;
+    registers.A = 0x20;
+
+    Calculator::TXA(&mut registers);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn TXA_update_negative_test() {
+    let mut registers = Registers::new();
+    registers.X = 0x90; // Nagative value(over than 0x80)
+
+    Calculator::TXA(&mut registers);
+    assert_eq!(registers.A, 0x90);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
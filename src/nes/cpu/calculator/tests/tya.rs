@@ -0,0 +1,36 @@
+use super::*;
+
+#[test]
+fn TYA_test() {
+    let mut registers = Registers::new();
+    registers.Y = 0x20;
+    registers.A = 0x00;
+
+    Calculator::TYA(&mut registers);
+    assert_eq!(registers.A, 0x20);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, false);
+}
+
+#[test]
+fn TYA_update_zero_test() {
+    let mut registers = Registers::new();
+    registers.Y = 0x00;
+    registers.A = 0x20;
+
+    Calculator::TYA(&mut registers);
+    assert_eq!(registers.A, 0x00);
+    assert_eq!(registers.P.negative, false);
+    assert_eq!(registers.P.zero, true);
+}
+
+#[test]
+fn TYA_update_negative_test() {
+    let mut registers = Registers::new();
+    registers.Y = 0x90; // Nagative value(over than 0x80)
+
+    Calculator::TYA(&mut registers);
+    assert_eq!(registers.A, 0x90);
+    assert_eq!(registers.P.negative, true);
+    assert_eq!(registers.P.zero, false);
+}
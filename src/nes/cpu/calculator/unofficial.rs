@@ -0,0 +1,93 @@
+use nes::cpu::bus::CpuBus;
+use nes::cpu::registers::Registers;
+use nes::cpu::variant::Variant;
+
+use super::Calculator;
+
+impl Calculator {
+    pub(super) fn LAX<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        let data = bus.read(opeland);
+        registers.A = data;
+        registers.X = data;
+        Calculator::update_negative_and_zero(registers, data);
+    }
+
+    pub(super) fn SAX<T: CpuBus>(registers: &Registers, bus: &mut T, opeland: u16) {
+        bus.write(opeland, registers.A & registers.X);
+    }
+
+    pub(super) fn DCP<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        // DEC + CMP
+        let data = Calculator::read_for_modify(bus, opeland).wrapping_sub(1);
+        bus.write(opeland, data);
+
+        let target = registers.A;
+        Calculator::compare(registers, target, data);
+    }
+
+    pub(super) fn ISC<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16, variant: &Variant) {
+        // INC + SBC
+        let data = Calculator::read_for_modify(bus, opeland).wrapping_add(1);
+        bus.write(opeland, data);
+        Calculator::SBC_immediate(registers, data as u16, variant);
+    }
+
+    pub(super) fn SLO<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        // ASL + ORA
+        let data = Calculator::shift_left(registers, Calculator::read_for_modify(bus, opeland));
+        bus.write(opeland, data);
+        Calculator::ORA_immediate(registers, data as u16);
+    }
+
+    pub(super) fn RLA<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        // ROL + AND
+        let data = Calculator::rotate_left(registers, Calculator::read_for_modify(bus, opeland));
+        bus.write(opeland, data);
+        Calculator::AND_immediate(registers, data as u16);
+    }
+
+    pub(super) fn SRE<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16) {
+        // LSR + EOR
+        let data = Calculator::shift_right(registers, Calculator::read_for_modify(bus, opeland));
+        bus.write(opeland, data);
+        Calculator::EOR_immediate(registers, data as u16);
+    }
+
+    pub(super) fn RRA<T: CpuBus>(registers: &mut Registers, bus: &mut T, opeland: u16, variant: &Variant) {
+        // ROR + ADC, ADC uses the carry shifted out by ROR.
+        let data = Calculator::rotate_right(registers, Calculator::read_for_modify(bus, opeland));
+        bus.write(opeland, data);
+        Calculator::ADC_immediate(registers, data as u16, variant);
+    }
+
+    pub(super) fn ANC(registers: &mut Registers, opeland: u16) {
+        // AND #imm, then bit 7 is copied to carry.
+        Calculator::AND_immediate(registers, opeland);
+        registers.P.carry = registers.P.negative;
+    }
+
+    pub(super) fn ALR(registers: &mut Registers, opeland: u16) {
+        // AND #imm + LSR A
+        Calculator::AND_immediate(registers, opeland);
+        Calculator::LSR_accumulator(registers);
+    }
+
+    pub(super) fn ARR(registers: &mut Registers, opeland: u16) {
+        // AND #imm + ROR A, carry is bit 6 and overflow is bit 6 xor bit 5 of the result.
+        Calculator::AND_immediate(registers, opeland);
+        Calculator::ROR_accumulator(registers);
+
+        let data = registers.A;
+        registers.P.carry = (data & 0x40) == 0x40;
+        registers.P.overflow = ((data >> 6) ^ (data >> 5)) & 0x01 == 0x01;
+    }
+
+    pub(super) fn AXS(registers: &mut Registers, opeland: u16) {
+        // X = (A & X) - #imm, flags are set like CMP.
+        let target = registers.A & registers.X;
+        let data = opeland as u8;
+
+        Calculator::compare(registers, target, data);
+        registers.X = target.wrapping_sub(data);
+    }
+}
@@ -2,54 +2,108 @@ use super::bus::CpuBus;
 use super::registers::Registers;
 use super::AddressingMode;
 
+pub const NMI_VECTOR: u16 = 0xFFFA;
+pub const RESET_VECTOR: u16 = 0xFFFC;
+pub const IRQ_VECTOR: u16 = 0xFFFE;
+
 pub struct Controller;
 
 impl Controller {
     pub fn reset<T: CpuBus>(registers: &mut Registers, bus: &mut T) {
         registers.reset();
-        registers.PC = bus.read_twice(0xFFFC);
+        registers.PC = bus.read_twice(RESET_VECTOR);
+    }
+
+    pub fn interrupt<T: CpuBus>(registers: &mut Registers, bus: &mut T, vector: u16, break_mode: bool) {
+        // B flag is only set in the pushed status, when it is pushed by BRK.
+        let pc = registers.PC;
+        let status = registers.P.to_bit() & 0xEF | 0x20 | (break_mode as u8) << 4;
+
+        Controller::push_twice(registers, bus, pc);
+        Controller::push(registers, bus, status);
+        registers.P.interrupt = true;
+        registers.PC = bus.read_twice(vector);
+    }
+
+    pub fn push<T: CpuBus>(registers: &mut Registers, bus: &mut T, data: u8) {
+        // the stack is fixed to page 0x01 and grows downward.
+        bus.write(0x0100 | registers.S as u16, data);
+        registers.S = registers.S.wrapping_sub(1);
+    }
+
+    pub fn pop<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u8 {
+        registers.S = registers.S.wrapping_add(1);
+        bus.read(0x0100 | registers.S as u16)
+    }
+
+    /// Reads the top of the stack without popping, before the stack pointer is incremented.
+    pub fn read_stack<T: CpuBus>(registers: &Registers, bus: &mut T) {
+        bus.dummy_read(0x0100 | registers.S as u16);
+    }
+
+    pub fn push_twice<T: CpuBus>(registers: &mut Registers, bus: &mut T, data: u16) {
+        Controller::push(registers, bus, (data >> 8) as u8);
+        Controller::push(registers, bus, data as u8);
+    }
+
+    pub fn pop_twice<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
+        let lower = Controller::pop(registers, bus) as u16;
+        let upper = Controller::pop(registers, bus) as u16;
+        lower | upper << 8
     }
 
     pub fn fetch<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u8 {
-        let code = bus.read(registers.PC);
-        registers.PC += 1;
+        let code = bus.fetch(registers.PC);
+        registers.PC = registers.PC.wrapping_add(1);
         code
     }
 
-    pub fn fetch_opeland<T: CpuBus>(registers: &mut Registers, bus: &mut T, mode: &AddressingMode) -> u16 {
+    /// Returns the opeland and whether indexing crossed a page boundary.
+    pub fn fetch_opeland<T: CpuBus>(registers: &mut Registers, bus: &mut T, mode: &AddressingMode) -> (u16, bool) {
         match *mode {
-            AddressingMode::Implied => 0x0000,
-            AddressingMode::Accumulator => 0x0000,
-            AddressingMode::Immediate => Controller::fetch(registers, bus) as u16,
-            AddressingMode::Relative => Controller::fetch_relative(registers, bus),
-            AddressingMode::ZeroPage => Controller::fetch(registers, bus) as u16,
-            AddressingMode::ZeroPageX => Controller::fetch_page_zero_x(registers, bus),
-            AddressingMode::ZeroPageY => Controller::fetch_page_zero_y(registers, bus),
-            AddressingMode::Absolute => Controller::fetch_absolute(registers, bus),
+            AddressingMode::Implied => (Controller::read_next(registers, bus), false),
+            AddressingMode::Accumulator => (Controller::read_next(registers, bus), false),
+            AddressingMode::Immediate => (Controller::fetch(registers, bus) as u16, false),
+            AddressingMode::Relative => (Controller::fetch_relative(registers, bus), false),
+            AddressingMode::ZeroPage => (Controller::fetch(registers, bus) as u16, false),
+            AddressingMode::ZeroPageX => (Controller::fetch_page_zero_x(registers, bus), false),
+            AddressingMode::ZeroPageY => (Controller::fetch_page_zero_y(registers, bus), false),
+            AddressingMode::Absolute => (Controller::fetch_absolute(registers, bus), false),
             AddressingMode::AbsoluteX => Controller::fetch_absolute_x(registers, bus),
             AddressingMode::AbsoluteY => Controller::fetch_absolute_y(registers, bus),
-            AddressingMode::PreIndexedIndirect => Controller::fetch_pre_indexed_indirect(registers, bus),
+            AddressingMode::PreIndexedIndirect => (Controller::fetch_pre_indexed_indirect(registers, bus), false),
             AddressingMode::PostIndexedIndirect => Controller::fetch_post_indexed_indirect(registers, bus),
-            AddressingMode::IndirectAbsolute => Controller::fetch_indirect_absolute(registers, bus),
+            AddressingMode::IndirectAbsolute => (Controller::fetch_indirect_absolute(registers, bus), false),
         }
     }
 
+    fn read_next<T: CpuBus>(registers: &Registers, bus: &mut T) -> u16 {
+        // 1 byte instructions read the next byte, and don't use it.
+        bus.dummy_read(registers.PC);
+        0x0000
+    }
+
     pub fn fetch_relative<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
         let offset = Controller::fetch(registers, bus) as u16;
 
         if offset < 0x80 {
-            registers.PC + offset
+            registers.PC.wrapping_add(offset)
         } else {
-            registers.PC + offset - 0x100
+            registers.PC.wrapping_add(offset).wrapping_sub(0x100)
         }
     }
 
     pub fn fetch_page_zero_x<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
-        (Controller::fetch(registers, bus) + registers.X) as u16
+        let addr = Controller::fetch(registers, bus);
+        // the base address is read while the index is added.
+        bus.dummy_read(addr as u16);
+        addr.wrapping_add(registers.X) as u16
     }
 
     pub fn fetch_page_zero_y<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
-        (Controller::fetch(registers, bus) + registers.X) as u16
+        let addr = Controller::fetch(registers, bus);
+        bus.dummy_read(addr as u16);
+        addr.wrapping_add(registers.Y) as u16
     }
 
     pub fn fetch_absolute<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
@@ -58,23 +112,46 @@ impl Controller {
         lower | upper << 8
     }
 
-    pub fn fetch_absolute_x<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
-        Controller::fetch_absolute(registers, bus) + registers.X as u16
+    pub fn fetch_absolute_x<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> (u16, bool) {
+        let base = Controller::fetch_absolute(registers, bus);
+        Controller::add_index(base, registers.X)
+    }
+
+    pub fn fetch_absolute_y<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> (u16, bool) {
+        let base = Controller::fetch_absolute(registers, bus);
+        Controller::add_index(base, registers.Y)
     }
 
-    pub fn fetch_absolute_y<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
-        Controller::fetch_absolute(registers, bus) + registers.Y as u16
+    fn add_index(base: u16, index: u8) -> (u16, bool) {
+        let addr = base.wrapping_add(index as u16);
+        let page_crossed = (base & 0xFF00) != (addr & 0xFF00);
+        (addr, page_crossed)
     }
 
     pub fn fetch_pre_indexed_indirect<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
         let fetch_data = Controller::fetch(registers, bus);
-        let addr = (fetch_data + registers.X) as u16;
-        bus.read_twice(addr)
+        bus.dummy_read(fetch_data as u16);
+        let addr = fetch_data.wrapping_add(registers.X);
+        Controller::read_zero_page_twice(bus, addr)
+    }
+
+    pub fn fetch_post_indexed_indirect<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> (u16, bool) {
+        let addr = Controller::fetch(registers, bus);
+        let base = Controller::read_zero_page_twice(bus, addr);
+        Controller::add_index(base, registers.Y)
+    }
+
+    /// Indexed addressing reads the address before the carry is added to the upper byte.
+    pub fn read_unfixed_address<T: CpuBus>(bus: &mut T, addr: u16, page_crossed: bool) {
+        let unfixed = if page_crossed { addr.wrapping_sub(0x100) } else { addr };
+        bus.dummy_read(unfixed);
     }
 
-    pub fn fetch_post_indexed_indirect<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
-        let addr = Controller::fetch(registers, bus) as u16;
-        bus.read_twice(addr) + registers.Y as u16
+    fn read_zero_page_twice<T: CpuBus>(bus: &mut T, addr: u8) -> u16 {
+        // the pointer doesn't leave the zero page: 0x00FF -> 0x0000
+        let lower = bus.read(addr as u16) as u16;
+        let upper = bus.read(addr.wrapping_add(1) as u16) as u16;
+        lower | upper << 8
     }
 
     pub fn fetch_indirect_absolute<T: CpuBus>(registers: &mut Registers, bus: &mut T) -> u16 {
@@ -105,7 +182,7 @@ mod controller_test {
 
     impl BusMock {
         fn new() -> Self {
-            Self { ram: vec![0; 0xFFFF] }
+            Self { ram: vec![0; 0x10000] }
         }
     }
 
@@ -209,7 +286,7 @@ mod controller_test {
         bus.ram[0x0011] = 0x56; // upper address
 
         // expect value is 0x5673(read from bus) + 0x10(read from Y of the registers)
-        assert_eq!(Controller::fetch_post_indexed_indirect(&mut registers, &mut bus), 0x5678);
+        assert_eq!(Controller::fetch_post_indexed_indirect(&mut registers, &mut bus), (0x5678, false));
         assert_eq!(registers.PC, 0x0001);
     }
 
@@ -284,4 +361,178 @@ mod controller_test {
         assert_eq!(Controller::fetch_indirect_absolute(&mut registers, &mut bus), 0x0020);
         assert_eq!(registers.PC, 0x0002);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fetch_page_zero_x_wrap_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        registers.X = 0x20;
+        registers.PC = 0x0000;
+        bus.ram[0x0000] = 0xF0;
+
+        // 0xF0 + 0x20 doesn't leave the zero page.
+        assert_eq!(Controller::fetch_page_zero_x(&mut registers, &mut bus), 0x0010);
+        assert_eq!(registers.PC, 0x0001);
+    }
+
+    #[test]
+    fn fetch_page_zero_y_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        registers.X = 0x01;
+        registers.Y = 0x05;
+        registers.PC = 0x0000;
+        bus.ram[0x0000] = 0x10;
+
+        assert_eq!(Controller::fetch_page_zero_y(&mut registers, &mut bus), 0x0015);
+    }
+
+    #[test]
+    fn fetch_absolute_x_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        registers.X = 0x10;
+        registers.PC = 0x0000;
+        bus.ram[0x0000] = 0xF8; // lower address
+        bus.ram[0x0001] = 0x12; // upper address
+
+        // 0x12F8 + 0x10 crosses the page.
+        assert_eq!(Controller::fetch_absolute_x(&mut registers, &mut bus), (0x1308, true));
+        assert_eq!(registers.PC, 0x0002);
+    }
+
+    #[test]
+    fn fetch_pre_indexed_indirect_wrap_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        registers.X = 0x01;
+        registers.PC = 0x0100;
+        bus.ram[0x0100] = 0xFE;
+
+        // 0xFE + X = 0xFF, the upper address is read from 0x0000.
+        bus.ram[0x00FF] = 0x78; // lower address
+        bus.ram[0x0000] = 0x56; // upper address
+
+        assert_eq!(Controller::fetch_pre_indexed_indirect(&mut registers, &mut bus), 0x5678);
+    }
+
+    #[test]
+    fn fetch_post_indexed_indirect_wrap_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        registers.Y = 0x10;
+        registers.PC = 0x0100;
+        bus.ram[0x0100] = 0xFF;
+
+        bus.ram[0x00FF] = 0xF8; // lower address
+        bus.ram[0x0000] = 0x56; // upper address(not carry up to 0x0100)
+
+        assert_eq!(Controller::fetch_post_indexed_indirect(&mut registers, &mut bus), (0x5708, true));
+    }
+
+    #[test]
+    fn push_and_pop_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        registers.S = 0xFD;
+        Controller::push(&mut registers, &mut bus, 0x12);
+        assert_eq!(bus.ram[0x01FD], 0x12);
+        assert_eq!(registers.S, 0xFC);
+
+        assert_eq!(Controller::pop(&mut registers, &mut bus), 0x12);
+        assert_eq!(registers.S, 0xFD);
+    }
+
+    #[test]
+    fn push_and_pop_wrap_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        // the stack pointer wraps in page 0x01.
+        registers.S = 0x00;
+        Controller::push(&mut registers, &mut bus, 0x12);
+        assert_eq!(bus.ram[0x0100], 0x12);
+        assert_eq!(registers.S, 0xFF);
+
+        assert_eq!(Controller::pop(&mut registers, &mut bus), 0x12);
+        assert_eq!(registers.S, 0x00);
+    }
+
+    #[test]
+    fn push_twice_and_pop_twice_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        registers.S = 0xFD;
+        Controller::push_twice(&mut registers, &mut bus, 0x1234);
+        assert_eq!(bus.ram[0x01FD], 0x12); // upper address is pushed first
+        assert_eq!(bus.ram[0x01FC], 0x34);
+        assert_eq!(registers.S, 0xFB);
+
+        assert_eq!(Controller::pop_twice(&mut registers, &mut bus), 0x1234);
+        assert_eq!(registers.S, 0xFD);
+    }
+
+    #[test]
+    fn interrupt_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        bus.write(0xFFFA, 0x78); // lower address
+        bus.write(0xFFFB, 0x56); // upper address
+
+        registers.PC = 0x1234;
+        registers.S = 0xFD;
+        registers.P.set_by_bit(0b11000011);
+
+        Controller::interrupt(&mut registers, &mut bus, NMI_VECTOR, false);
+        assert_eq!(registers.PC, 0x5678);
+        assert_eq!(registers.S, 0xFA);
+        assert_eq!(registers.P.interrupt, true);
+        assert_eq!(bus.ram[0x01FD], 0x12);
+        assert_eq!(bus.ram[0x01FC], 0x34);
+        assert_eq!(bus.ram[0x01FB], 0b11100011); // B flag is cleared
+
+        registers.S = 0xFD;
+        Controller::interrupt(&mut registers, &mut bus, IRQ_VECTOR, true);
+        assert_eq!(bus.ram[0x01FB] & 0x10, 0x10); // B flag is set by BRK
+    }
+
+    #[test]
+    fn fetch_absolute_y_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        registers.Y = 0x10;
+        registers.PC = 0x0000;
+        bus.ram[0x0000] = 0x00; // lower address
+        bus.ram[0x0001] = 0x12; // upper address
+
+        assert_eq!(Controller::fetch_absolute_y(&mut registers, &mut bus), (0x1210, false));
+    }
+
+    #[test]
+    fn fetch_opeland_page_crossed_test() {
+        let mut registers = Registers::new();
+        let mut bus = BusMock::new();
+
+        registers.X = 0x01;
+        registers.PC = 0x0000;
+        bus.ram[0x0000] = 0xFF; // lower address
+        bus.ram[0x0001] = 0x12; // upper address
+
+        let opeland = Controller::fetch_opeland(&mut registers, &mut bus, &AddressingMode::AbsoluteX);
+        assert_eq!(opeland, (0x1300, true));
+
+        // the same address without index doesn't cross the page.
+        registers.PC = 0x0000;
+        let opeland = Controller::fetch_opeland(&mut registers, &mut bus, &AddressingMode::Absolute);
+        assert_eq!(opeland, (0x12FF, false));
+    }
+}
@@ -0,0 +1,122 @@
+use super::bus::CpuBus;
+
+/// Wraps a bus to make every access take 1 CPU cycle.
+/// The wrapped bus is ticked before each access, so the other chips can catch up with the CPU.
+pub struct CycleBus<'a, T: 'a> where T: CpuBus {
+    bus: &'a mut T,
+    cycle: usize,
+}
+
+impl <'a, T: 'a> CycleBus<'a, T> where T: CpuBus {
+    pub fn new(bus: &'a mut T) -> Self {
+        Self {
+            bus: bus,
+            cycle: 0,
+        }
+    }
+
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    fn step(&mut self) {
+        self.bus.tick();
+        self.cycle += 1;
+    }
+}
+
+impl <'a, T: 'a> CpuBus for CycleBus<'a, T> where T: CpuBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.step();
+        self.bus.read(addr)
+    }
+
+    fn fetch(&mut self, addr: u16) -> u8 {
+        self.step();
+        self.bus.fetch(addr)
+    }
+
+    fn read_twice(&mut self, addr: u16) -> u16 {
+        let lower = self.read(addr) as u16;
+        let upper = self.read(addr.wrapping_add(1)) as u16;
+        lower | upper << 8
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.step();
+        self.bus.write(addr, data);
+    }
+
+    // the real CPU accesses the bus even when it throws the data away.
+    fn dummy_read(&mut self, addr: u16) {
+        self.read(addr);
+    }
+
+    fn dummy_write(&mut self, addr: u16, data: u8) {
+        self.write(addr, data);
+    }
+
+    fn peek(&mut self, addr: u16) -> u8 {
+        self.bus.peek(addr)
+    }
+
+    fn ppu_position(&self) -> (usize, usize) {
+        self.bus.ppu_position()
+    }
+}
+
+#[cfg(test)]
+mod cycle_bus_test {
+    use super::*;
+
+    struct BusMock {
+        ram: Vec<u8>,
+        ticks: usize,
+    }
+
+    impl BusMock {
+        fn new() -> Self {
+            Self { ram: vec![0; 0x10000], ticks: 0 }
+        }
+    }
+
+    impl CpuBus for BusMock {
+        fn read(&mut self, addr: u16) -> u8 {
+            self.ram[addr as usize]
+        }
+
+        fn read_twice(&mut self, addr: u16) -> u16 {
+            let lower = self.ram[addr as usize] as u16;
+            let upper = self.ram[addr as usize + 1] as u16;
+            lower | upper << 8
+        }
+
+        fn write(&mut self, addr: u16, data: u8) {
+            self.ram[addr as usize] = data;
+        }
+
+        fn tick(&mut self) {
+            self.ticks += 1;
+        }
+    }
+
+    #[test]
+    fn cycle_test() {
+        let mut bus = BusMock::new();
+        bus.ram[0x0010] = 0x34;
+        bus.ram[0x0011] = 0x12;
+
+        {
+            let mut cycle_bus = CycleBus::new(&mut bus);
+            assert_eq!(cycle_bus.read_twice(0x0010), 0x1234);
+            cycle_bus.dummy_read(0x0010);
+            cycle_bus.dummy_write(0x0020, 0x56);
+            cycle_bus.write(0x0021, 0x78);
+            assert_eq!(cycle_bus.cycle(), 5);
+        }
+
+        assert_eq!(bus.ticks, 5);
+        assert_eq!(bus.ram[0x0020], 0x56);
+        assert_eq!(bus.ram[0x0021], 0x78);
+    }
+}
@@ -0,0 +1,303 @@
+use std::rc::Rc;
+
+use super::bus::CpuBus;
+use super::opecode::{self, AddressingMode, Command};
+use super::registers::Registers;
+
+/// Names of addresses, like the symbols of the assembler.
+pub trait Labels {
+    fn label(&self, addr: u16) -> Option<&str>;
+}
+
+impl<L: Labels + ?Sized> Labels for Rc<L> {
+    fn label(&self, addr: u16) -> Option<&str> {
+        (**self).label(addr)
+    }
+}
+
+pub struct NoLabels;
+
+impl Labels for NoLabels {
+    fn label(&self, _addr: u16) -> Option<&str> {
+        None
+    }
+}
+
+/// How operands are written.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Syntax {
+    /// The absolute addressing of the zero page is marked with `a:` to be assembled again.
+    Ca65,
+    /// The addresses are written as they are, like Nintendulator and nestest.log.
+    Nintendulator,
+}
+
+/// Disassembles the instruction at `addr` into ca65 syntax.
+/// Returns the text and the byte length of the instruction.
+/// The branch destination is resolved to the absolute address, and the addresses of operands are shown by their labels.
+pub fn disassemble_with_labels<T: CpuBus, L: Labels + ?Sized>(bus: &mut T, addr: u16, labels: &L) -> (String, u16) {
+    disassemble_with_syntax(bus, addr, labels, Syntax::Ca65)
+}
+
+/// Disassembles like `disassemble_with_labels` in `syntax`.
+pub fn disassemble_with_syntax<T: CpuBus, L: Labels + ?Sized>(bus: &mut T, addr: u16, labels: &L, syntax: Syntax) -> (String, u16) {
+    let code = bus.peek(addr);
+    let rule = match opecode::decode(code) {
+        Some(rule) => rule,
+        None => return (format!(".byte ${:02X}", code), 1),
+    };
+
+    let lower = bus.peek(addr.wrapping_add(1));
+    let upper = bus.peek(addr.wrapping_add(2));
+    let absolute = (upper as u16) << 8 | lower as u16;
+    let absolute_operand = |labels: &L| match syntax {
+        Syntax::Ca65 => force_absolute(labels, absolute),
+        Syntax::Nintendulator => absolute_address(labels, absolute),
+    };
+
+    let operand = match rule.mode {
+        AddressingMode::Implied => String::new(),
+        AddressingMode::Accumulator => String::from(" A"),
+        AddressingMode::Immediate => format!(" #${:02X}", lower),
+        AddressingMode::ZeroPage => format!(" {}", zero_page(labels, lower)),
+        AddressingMode::ZeroPageX => format!(" {},X", zero_page(labels, lower)),
+        AddressingMode::ZeroPageY => format!(" {},Y", zero_page(labels, lower)),
+        AddressingMode::Relative => format!(" {}", absolute_address(labels, branch_destination(addr, lower))),
+        AddressingMode::Absolute => format!(" {}", absolute_operand(labels)),
+        AddressingMode::AbsoluteX => format!(" {},X", absolute_operand(labels)),
+        AddressingMode::AbsoluteY => format!(" {},Y", absolute_operand(labels)),
+        AddressingMode::IndirectAbsolute => format!(" ({})", absolute_address(labels, absolute)),
+        AddressingMode::PreIndexedIndirect => format!(" ({},X)", zero_page(labels, lower)),
+        AddressingMode::PostIndexedIndirect => format!(" ({}),Y", zero_page(labels, lower)),
+    };
+
+    (format!("{}{}", mnemonic(&rule.command), operand), rule.length)
+}
+
+/// Disassembles the instruction at PC like `disassemble_with_labels`.
+/// The effective address of the indexed and the indirect addressing, which is resolved by the registers,
+/// and the data in it are appended as the comment, like `LDA ($10),Y ; $0334 = $89`.
+/// Only the destination is appended to JMP, like `JMP ($10FF) ; $8000`.
+pub fn disassemble_at_pc<T: CpuBus, L: Labels + ?Sized>(registers: &Registers, bus: &mut T, labels: &L) -> (String, u16) {
+    let pc = registers.PC;
+    let (text, length) = disassemble_with_labels(bus, pc, labels);
+    let jump = opecode::decode(bus.peek(pc)).map_or(false, |rule| rule.mode == AddressingMode::IndirectAbsolute);
+    match effective_address(registers, bus) {
+        Some(effective) if jump => (format!("{} ; {}", text, absolute_address(labels, effective.addr)), length),
+        Some(effective) => (format!("{} ; {} = ${:02X}", text, absolute_address(labels, effective.addr), bus.peek(effective.addr)), length),
+        None => (text, length),
+    }
+}
+
+/// The address which the indexed or the indirect addressing resolves.
+#[derive(Debug, PartialEq)]
+pub struct EffectiveAddress {
+    /// The accessed address, or the destination of JMP.
+    pub addr: u16,
+    /// The pointer in the zero page of `(zp,X)`, or the address read from the zero page of `(zp),Y`.
+    pub indirect: Option<u16>,
+}
+
+/// The address which the indexed or the indirect addressing of the instruction at PC accesses.
+/// The destination of JMP is returned for the indirect addressing.
+pub fn effective_address<T: CpuBus>(registers: &Registers, bus: &mut T) -> Option<EffectiveAddress> {
+    let pc = registers.PC;
+    let rule = opecode::decode(bus.peek(pc))?;
+    let operand = bus.peek(pc.wrapping_add(1));
+    let absolute = (bus.peek(pc.wrapping_add(2)) as u16) << 8 | operand as u16;
+
+    let (addr, indirect) = match rule.mode {
+        AddressingMode::ZeroPageX => (operand.wrapping_add(registers.X) as u16, None),
+        AddressingMode::ZeroPageY => (operand.wrapping_add(registers.Y) as u16, None),
+        AddressingMode::AbsoluteX => (absolute.wrapping_add(registers.X as u16), None),
+        AddressingMode::AbsoluteY => (absolute.wrapping_add(registers.Y as u16), None),
+        AddressingMode::IndirectAbsolute => {
+            // the upper byte is read from the same page.
+            let upper_addr = (absolute & 0xFF00) | (absolute.wrapping_add(1) & 0x00FF);
+            ((bus.peek(upper_addr) as u16) << 8 | bus.peek(absolute) as u16, None)
+        },
+        AddressingMode::PreIndexedIndirect => {
+            let pointer = operand.wrapping_add(registers.X);
+            (read_zero_page_pointer(bus, pointer), Some(pointer as u16))
+        },
+        AddressingMode::PostIndexedIndirect => {
+            let base = read_zero_page_pointer(bus, operand);
+            (base.wrapping_add(registers.Y as u16), Some(base))
+        },
+        _ => return None,
+    };
+    Some(EffectiveAddress { addr: addr, indirect: indirect })
+}
+
+/// Reads the pointer in the zero page, the upper byte of 0xFF is read from 0x00.
+fn read_zero_page_pointer<T: CpuBus>(bus: &mut T, pointer: u8) -> u16 {
+    let lower = bus.peek(pointer as u16) as u16;
+    let upper = bus.peek(pointer.wrapping_add(1) as u16) as u16;
+    upper << 8 | lower
+}
+
+fn zero_page<L: Labels + ?Sized>(labels: &L, addr: u8) -> String {
+    match labels.label(addr as u16) {
+        Some(label) => String::from(label),
+        None => format!("${:02X}", addr),
+    }
+}
+
+fn absolute_address<L: Labels + ?Sized>(labels: &L, addr: u16) -> String {
+    match labels.label(addr) {
+        Some(label) => String::from(label),
+        None => format!("${:04X}", addr),
+    }
+}
+
+/// The absolute addressing of the address in the zero page is marked with `a:`,
+/// otherwise ca65 assembles it into the zero page addressing.
+fn force_absolute<L: Labels + ?Sized>(labels: &L, addr: u16) -> String {
+    let operand = absolute_address(labels, addr);
+    if addr < 0x0100 {
+        format!("a:{}", operand)
+    } else {
+        operand
+    }
+}
+
+fn mnemonic(command: &Command) -> String {
+    match *command {
+        // ca65 calls KIL as JAM.
+        Command::KIL => String::from("JAM"),
+        _ => format!("{:?}", command),
+    }
+}
+
+fn branch_destination(addr: u16, offset: u8) -> u16 {
+    // the offset is relative to the next instruction.
+    addr.wrapping_add(2).wrapping_add(offset as i8 as u16)
+}
+
+#[cfg(test)]
+mod disassembler_test {
+    use super::*;
+
+    struct BusMock {
+        ram: Vec<u8>,
+    }
+
+    impl BusMock {
+        fn new(program: &[u8]) -> Self {
+            let mut ram = vec![0; 0x10000];
+            ram[0x8000..0x8000 + program.len()].copy_from_slice(program);
+            Self { ram: ram }
+        }
+    }
+
+    impl CpuBus for BusMock {
+        fn read(&mut self, addr: u16) -> u8 {
+            self.ram[addr as usize]
+        }
+
+        fn read_twice(&mut self, addr: u16) -> u16 {
+            let lower = self.ram[addr as usize] as u16;
+            let upper = self.ram[addr as usize + 1] as u16;
+            lower | upper << 8
+        }
+
+        fn write(&mut self, addr: u16, data: u8) {
+            self.ram[addr as usize] = data;
+        }
+    }
+
+    fn assert_disassemble(program: &[u8], expected: &str) {
+        let mut bus = BusMock::new(program);
+        assert_eq!(disassemble_with_labels(&mut bus, 0x8000, &NoLabels), (String::from(expected), program.len() as u16));
+    }
+
+    #[test]
+    fn disassemble_test() {
+        assert_disassemble(&[0xEA], "NOP");
+        assert_disassemble(&[0x0A], "ASL A");
+        assert_disassemble(&[0xA9, 0x10], "LDA #$10");
+        assert_disassemble(&[0xA5, 0x10], "LDA $10");
+        assert_disassemble(&[0xB5, 0x10], "LDA $10,X");
+        assert_disassemble(&[0xB6, 0x10], "LDX $10,Y");
+        assert_disassemble(&[0xAD, 0x34, 0x12], "LDA $1234");
+        assert_disassemble(&[0xBD, 0x34, 0x12], "LDA $1234,X");
+        assert_disassemble(&[0xB9, 0x34, 0x12], "LDA $1234,Y");
+        assert_disassemble(&[0xAD, 0x10, 0x00], "LDA a:$0010");
+        assert_disassemble(&[0x9D, 0x10, 0x00], "STA a:$0010,X");
+        assert_disassemble(&[0x6C, 0x34, 0x12], "JMP ($1234)");
+        assert_disassemble(&[0xA1, 0x10], "LDA ($10,X)");
+        assert_disassemble(&[0xB1, 0x10], "LDA ($10),Y");
+    }
+
+    #[test]
+    fn disassemble_branch_test() {
+        assert_disassemble(&[0xD0, 0x10], "BNE $8012");
+        assert_disassemble(&[0xD0, 0xFE], "BNE $8000");
+        assert_disassemble(&[0xD0, 0x80], "BNE $7F82");
+    }
+
+    struct LabelsMock;
+
+    impl Labels for LabelsMock {
+        fn label(&self, addr: u16) -> Option<&str> {
+            match addr {
+                0x0010 => Some("pointer"),
+                0x8000 => Some("reset"),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn disassemble_with_labels_test() {
+        let mut bus = BusMock::new(&[0xD0, 0xFE]);
+        assert_eq!(disassemble_with_labels(&mut bus, 0x8000, &LabelsMock), (String::from("BNE reset"), 2));
+
+        let mut bus = BusMock::new(&[0xB1, 0x10]);
+        assert_eq!(disassemble_with_labels(&mut bus, 0x8000, &LabelsMock), (String::from("LDA (pointer),Y"), 2));
+
+        let mut bus = BusMock::new(&[0xAD, 0x34, 0x12]);
+        assert_eq!(disassemble_with_labels(&mut bus, 0x8000, &LabelsMock), (String::from("LDA $1234"), 3));
+
+        let mut bus = BusMock::new(&[0xAD, 0x10, 0x00]);
+        assert_eq!(disassemble_with_labels(&mut bus, 0x8000, &LabelsMock), (String::from("LDA a:pointer"), 3));
+    }
+
+    #[test]
+    fn disassemble_at_pc_test() {
+        let mut registers = Registers::new();
+        registers.PC = 0x8000;
+        registers.X = 0x02;
+        registers.Y = 0x34;
+
+        // LDA ($10),Y
+        let mut bus = BusMock::new(&[0xB1, 0x10]);
+        bus.write(0x0010, 0x00);
+        bus.write(0x0011, 0x03);
+        bus.write(0x0334, 0x89);
+        assert_eq!(disassemble_at_pc(&registers, &mut bus, &LabelsMock), (String::from("LDA (pointer),Y ; $0334 = $89"), 2));
+
+        // the pointer wraps around in the zero page.
+        let mut bus = BusMock::new(&[0xA1, 0xFD]);
+        bus.write(0x00FF, 0x34);
+        bus.write(0x0000, 0x12);
+        assert_eq!(effective_address(&registers, &mut bus), Some(EffectiveAddress { addr: 0x1234, indirect: Some(0x00FF) }));
+
+        // JMP ($10FF) reads the upper byte from $1000.
+        let mut bus = BusMock::new(&[0x6C, 0xFF, 0x10]);
+        bus.write(0x10FF, 0x00);
+        bus.write(0x1000, 0x80);
+        assert_eq!(disassemble_at_pc(&registers, &mut bus, &LabelsMock), (String::from("JMP ($10FF) ; reset"), 3));
+
+        // the operand is the effective address.
+        let mut bus = BusMock::new(&[0xAD, 0x34, 0x12]);
+        assert_eq!(disassemble_at_pc(&registers, &mut bus, &LabelsMock), (String::from("LDA $1234"), 3));
+    }
+
+    #[test]
+    fn disassemble_undefined_test() {
+        assert_disassemble(&[0x02], "JAM");
+        // XAA isn't supported
+        assert_disassemble(&[0x8B], ".byte $8B");
+    }
+}
@@ -0,0 +1,5 @@
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CpuError {
+    /// `opecode` at `pc` isn't decoded. PC points the next byte of it.
+    UnknownOpecode { pc: u16, opecode: u8 },
+}
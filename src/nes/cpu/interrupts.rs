@@ -0,0 +1,73 @@
+#[derive(Debug, PartialEq, Clone)]
+pub struct Interrupts {
+    nmi_line: bool,
+    nmi_pending: bool,
+    irq_line: bool,
+}
+
+impl Interrupts {
+    pub fn new() -> Self {
+        Interrupts {
+            nmi_line: false,
+            nmi_pending: false,
+            irq_line: false,
+        }
+    }
+
+    /// NMI is edge-triggered: only the transition to asserted is latched.
+    pub fn set_nmi_line(&mut self, asserted: bool) {
+        if asserted && !self.nmi_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_line = asserted;
+    }
+
+    /// IRQ is level-triggered: it stays requested while the line is asserted.
+    pub fn set_irq_line(&mut self, asserted: bool) {
+        self.irq_line = asserted;
+    }
+
+    pub fn take_nmi(&mut self) -> bool {
+        let pending = self.nmi_pending;
+        self.nmi_pending = false;
+        pending
+    }
+
+    pub fn is_irq_requested(&self) -> bool {
+        self.irq_line
+    }
+}
+
+#[cfg(test)]
+mod interrupts_test {
+    use super::*;
+
+    #[test]
+    fn nmi_is_edge_triggered_test() {
+        let mut interrupts = Interrupts::new();
+
+        interrupts.set_nmi_line(true);
+        assert_eq!(interrupts.take_nmi(), true);
+
+        // holding the line doesn't request again.
+        interrupts.set_nmi_line(true);
+        assert_eq!(interrupts.take_nmi(), false);
+
+        interrupts.set_nmi_line(false);
+        interrupts.set_nmi_line(true);
+        assert_eq!(interrupts.take_nmi(), true);
+    }
+
+    #[test]
+    fn irq_is_level_triggered_test() {
+        let mut interrupts = Interrupts::new();
+        assert_eq!(interrupts.is_irq_requested(), false);
+
+        interrupts.set_irq_line(true);
+        assert_eq!(interrupts.is_irq_requested(), true);
+        assert_eq!(interrupts.is_irq_requested(), true);
+
+        interrupts.set_irq_line(false);
+        assert_eq!(interrupts.is_irq_requested(), false);
+    }
+}
@@ -1,48 +1,251 @@
 #![allow(non_snake_case)]
 
 pub mod bus;
+pub mod disassembler;
+pub mod error;
+pub mod interrupts;
+pub mod observer;
+pub mod open_bus;
+pub mod profiler;
 pub mod registers;
+pub mod state;
+pub mod timing;
+pub mod tracer;
+pub mod variant;
 
 pub use super::cpu::bus::{Bus, CpuBus};
+pub use super::cpu::disassembler::{disassemble_at_pc, disassemble_with_labels, Labels};
+pub use super::cpu::error::CpuError;
+pub use super::cpu::interrupts::Interrupts;
+pub use super::cpu::observer::{Access, AccessKind, BusObserver};
+pub use super::cpu::open_bus::OpenBus;
+pub use super::cpu::profiler::Profiler;
 pub use super::cpu::registers::Registers;
+pub use super::cpu::state::CpuState;
+pub use super::cpu::timing::Timing;
+pub use super::cpu::tracer::Tracer;
+pub use super::cpu::variant::Variant;
 
 mod opecode;
 mod controller;
 mod calculator;
+mod cycle_bus;
+
+use std::rc::Rc;
 
 use self::opecode::AddressingMode;
 use self::calculator::Calculator;
-use self::controller::Controller;
+use self::controller::{Controller, NMI_VECTOR, IRQ_VECTOR};
+use self::cycle_bus::CycleBus;
+
+const INTERRUPT_CYCLE: usize = 7;
+// a jammed CPU doesn't progress, but the clock keeps running.
+const JAMMED_CYCLE: usize = 1;
+
+/// The state of `Cpu` which `restore` brings back. The tracer, the labels and the profiler aren't included.
+#[derive(Clone)]
+pub struct CpuSnapshot {
+    registers: Registers,
+    interrupts: Interrupts,
+    state: CpuState,
+    cycle: usize,
+}
 
 pub struct Cpu {
     pub registers: Registers,
+    pub variant: Variant,
+    pub timing: Timing,
+    pub interrupts: Interrupts,
+    pub state: CpuState,
+    cycle: usize,
+    tracer: Option<Box<dyn Tracer>>,
+    labels: Option<Rc<dyn Labels>>,
+    profiler: Option<Profiler>,
 }
 
 impl Cpu {
-    pub fn new() -> Self {
-        Self { registers: Registers::new() }
+    pub fn new(variant: Variant, timing: Timing) -> Self {
+        Self {
+            registers: Registers::new(),
+            variant: variant,
+            timing: timing,
+            interrupts: Interrupts::new(),
+            state: CpuState::Running,
+            cycle: 0,
+            tracer: None,
+            labels: None,
+            profiler: None,
+        }
+    }
+
+    /// Runs 1 instruction and returns the spent cycle.
+    /// The cycle-stepped CPU ticks the bus on every cycle of the instruction.
+    /// The error is returned with the cycle spent until it, which is counted in `cycle` as well.
+    pub fn run<T: CpuBus>(&mut self, bus: &mut T) -> Result<usize, (CpuError, usize)> {
+        let result = if self.timing.is_cycle_stepped() {
+            let mut cycle_bus = CycleBus::new(bus);
+            let result = self.step(&mut cycle_bus);
+            let cycle = cycle_bus.cycle();
+            result.map(|_| cycle).map_err(|(error, _)| (error, cycle))
+        } else {
+            self.step(bus)
+        };
+
+        self.cycle += match result {
+            Ok(cycle) | Err((_, cycle)) => cycle,
+        };
+        result
+    }
+
+    fn step<T: CpuBus>(&mut self, bus: &mut T) -> Result<usize, (CpuError, usize)> {
+        if self.state.is_jammed() {
+            bus.dummy_read(self.registers.PC);
+            return Ok(JAMMED_CYCLE);
+        }
+
+        let pc = self.registers.PC;
+
+        // interrupts are checked between instructions, NMI has priority over IRQ.
+        let vector = if self.interrupts.take_nmi() {
+            Some(NMI_VECTOR)
+        } else if self.interrupts.is_irq_requested() && !self.registers.P.interrupt {
+            Some(IRQ_VECTOR)
+        } else {
+            None
+        };
+
+        if let Some(vector) = vector {
+            Cpu::interrupt(&mut self.registers, bus, vector);
+            if let Some(ref mut profiler) = self.profiler {
+                profiler.interrupt(pc, self.registers.PC, self.registers.S, INTERRUPT_CYCLE);
+            }
+            return Ok(INTERRUPT_CYCLE);
+        }
+
+        if let Some(ref mut tracer) = self.tracer {
+            let labels = self.labels.as_ref().map(|labels| &**labels);
+            // a label is shown in its own line, like the assembler source.
+            if let Some(label) = labels.and_then(|labels| labels.label(pc)) {
+                tracer.trace(&format!("{}:", label));
+            }
+            tracer.trace(&tracer::format_line(&self.registers, bus, self.cycle, labels));
+        }
+
+        let opecode = bus.peek(pc);
+        let cycle = Calculator::execute(&mut self.registers, bus, &self.variant, &mut self.state)?;
+        if let Some(ref mut profiler) = self.profiler {
+            profiler.execute(pc, opecode, self.registers.PC, self.registers.S, cycle);
+        }
+        Ok(cycle)
     }
 
-    pub fn run<T: CpuBus>(&mut self, bus: &mut T) -> usize {
-        Calculator::execute(&mut self.registers, bus)
+    fn interrupt<T: CpuBus>(registers: &mut Registers, bus: &mut T, vector: u16) {
+        // the opecode and the next byte are read, and thrown away like BRK.
+        let pc = registers.PC;
+        bus.dummy_read(pc);
+        bus.dummy_read(pc);
+        Controller::interrupt(registers, bus, vector, false);
     }
 
     pub fn reset<T: CpuBus>(&mut self, bus: &mut T) {
+        self.state = CpuState::Running;
+        // the reset sequence takes the same cycle as interrupts.
+        self.cycle = INTERRUPT_CYCLE;
         Controller::reset(&mut self.registers, bus);
     }
+
+    pub fn snapshot(&self) -> CpuSnapshot {
+        CpuSnapshot {
+            registers: self.registers.clone(),
+            interrupts: self.interrupts.clone(),
+            state: self.state,
+            cycle: self.cycle,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &CpuSnapshot) {
+        self.registers = snapshot.registers.clone();
+        self.interrupts = snapshot.interrupts.clone();
+        self.state = snapshot.state;
+        self.cycle = snapshot.cycle;
+    }
+
+    pub fn state(&self) -> CpuState {
+        self.state
+    }
+
+    pub fn set_nmi(&mut self, asserted: bool) {
+        self.interrupts.set_nmi_line(asserted);
+    }
+
+    pub fn set_irq(&mut self, asserted: bool) {
+        self.interrupts.set_irq_line(asserted);
+    }
+
+    /// Spends `cycle` without executing, while the DMA uses the bus.
+    pub fn stall(&mut self, cycle: usize) {
+        self.cycle += cycle;
+    }
+
+    /// The CPU cycle spent since the reset.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// Sets the tracer which receives a nestest-style line for each instruction.
+    pub fn set_tracer(&mut self, tracer: Option<Box<dyn Tracer>>) {
+        self.tracer = tracer;
+    }
+
+    pub fn take_tracer(&mut self) -> Option<Box<dyn Tracer>> {
+        self.tracer.take()
+    }
+
+    /// Sets the labels which the trace shows instead of the addresses.
+    pub fn set_labels(&mut self, labels: Option<Rc<dyn Labels>>) {
+        self.labels = labels;
+    }
+
+    /// Sets the profiler which the cycles of every instruction and interrupt are attributed by.
+    pub fn set_profiler(&mut self, profiler: Option<Profiler>) {
+        self.profiler = profiler;
+    }
+
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    pub fn profiler_mut(&mut self) -> Option<&mut Profiler> {
+        self.profiler.as_mut()
+    }
+
+    pub fn take_profiler(&mut self) -> Option<Profiler> {
+        self.profiler.take()
+    }
+
+    /// Formats the report of the profiler with the labels.
+    pub fn profile_report(&self) -> Option<String> {
+        let labels = self.labels.as_ref().map(|labels| &**labels);
+        self.profiler.as_ref().map(|profiler| profiler.report(labels))
+    }
 }
 
 #[cfg(test)]
 mod cpu_test {
     use super::*;
+    use super::profiler::RoutineStats;
+    use super::opecode::{self, Command};
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
     struct BusMock {
         ram: Vec<u8>,
+        writes: Vec<(u16, u8)>,
     }
 
     impl BusMock {
         fn new() -> Self {
-            Self { ram: vec![0; 0xFFFF] }
+            Self { ram: vec![0; 0x10000], writes: vec![] }
         }
     }
 
@@ -59,23 +262,335 @@ mod cpu_test {
 
         fn write(&mut self, addr: u16, data: u8) {
             self.ram[addr as usize] = data;
+            self.writes.push((addr, data));
         }
     }
 
     #[test]
     fn test_new() {
-        let cpu = Cpu::new();
+        let cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
         assert_eq!(cpu.registers, Registers::new());
+        assert_eq!(cpu.variant, Variant::Ricoh2A03);
     }
 
     #[test]
     fn test_reset() {
-        let mut cpu = Cpu::new();
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        let mut bus = BusMock::new();
+        bus.write(0xFFFC, 0x00);
+        bus.write(0xFFFD, 0x80);
+
+        cpu.reset(&mut bus);
+        assert_eq!(cpu.registers.PC, 0x8000);
+    }
+
+    #[test]
+    fn test_run_decimal_mode() {
+        // SED; CLC; LDA #$19; ADC #$28
+        let program = [0xF8, 0x18, 0xA9, 0x19, 0x69, 0x28];
+
+        let mut bus = BusMock::new();
+        for (i, code) in program.iter().enumerate() {
+            bus.write(0x8000 + i as u16, *code);
+        }
+
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        for _ in 0..4 { cpu.run(&mut bus).unwrap(); }
+        assert_eq!(cpu.registers.A, 0x41);
+
+        let mut cpu = Cpu::new(Variant::Mos6502, Timing::Instruction);
+        for _ in 0..4 { cpu.run(&mut bus).unwrap(); }
+        assert_eq!(cpu.registers.A, 0x47);
+    }
+
+    #[test]
+    fn test_run_nmi() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        let mut bus = BusMock::new();
+        bus.write(0xFFFA, 0x00);
+        bus.write(0xFFFB, 0x90);
+
+        cpu.registers.PC = 0x8000;
+        cpu.registers.S = 0xFD;
+        cpu.set_nmi(true);
+
+        assert_eq!(cpu.run(&mut bus), Ok(7));
+        assert_eq!(cpu.registers.PC, 0x9000);
+        assert_eq!(cpu.registers.S, 0xFA);
+        assert_eq!(cpu.registers.P.interrupt, true);
+
+        // the held line doesn't take NMI again, NOP at 0x9000 is executed.
+        bus.write(0x9000, 0xEA);
+        cpu.set_nmi(true);
+        assert_eq!(cpu.run(&mut bus), Ok(2));
+        assert_eq!(cpu.registers.PC, 0x9001);
+    }
+
+    #[test]
+    fn test_run_irq() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        let mut bus = BusMock::new();
+        bus.write(0xFFFE, 0x00);
+        bus.write(0xFFFF, 0x90);
+        bus.write(0x8000, 0xEA); // NOP
+
+        // IRQ is masked by the interrupt flag.
+        cpu.registers.PC = 0x8000;
+        cpu.registers.P.interrupt = true;
+        cpu.set_irq(true);
+        assert_eq!(cpu.run(&mut bus), Ok(2));
+        assert_eq!(cpu.registers.PC, 0x8001);
+
+        cpu.registers.P.interrupt = false;
+        assert_eq!(cpu.run(&mut bus), Ok(7));
+        assert_eq!(cpu.registers.PC, 0x9000);
+        assert_eq!(cpu.registers.P.interrupt, true);
+    }
+
+    #[test]
+    fn test_run_brk_and_rti() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        let mut bus = BusMock::new();
+        bus.write(0xFFFE, 0x00);
+        bus.write(0xFFFF, 0x90);
+        bus.write(0x8000, 0x00); // BRK
+        bus.write(0x9000, 0x40); // RTI
+
+        cpu.registers.PC = 0x8000;
+        cpu.registers.P.interrupt = false;
+        cpu.registers.P.carry = true;
+
+        assert_eq!(cpu.run(&mut bus), Ok(7));
+        assert_eq!(cpu.registers.PC, 0x9000);
+        assert_eq!(cpu.registers.P.interrupt, true);
+
+        // return to the next of the padding byte.
+        assert_eq!(cpu.run(&mut bus), Ok(6));
+        assert_eq!(cpu.registers.PC, 0x8002);
+        assert_eq!(cpu.registers.P.interrupt, false);
+        assert_eq!(cpu.registers.P.carry, true);
+    }
+
+    #[test]
+    fn test_run_jam() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
         let mut bus = BusMock::new();
         bus.write(0xFFFC, 0x00);
         bus.write(0xFFFD, 0x80);
+        bus.write(0x8000, 0xEA); // NOP
+        bus.write(0x8001, 0x02); // KIL
+
+        cpu.registers.PC = 0x8000;
+        cpu.run(&mut bus).unwrap();
+        cpu.run(&mut bus).unwrap();
+        assert_eq!(cpu.state(), CpuState::Jammed { pc: 0x8001, opecode: 0x02 });
+        assert_eq!(cpu.registers.PC, 0x8001);
+
+        // the jammed CPU ignores interrupts and doesn't fetch.
+        cpu.set_nmi(true);
+        assert_eq!(cpu.run(&mut bus), Ok(1));
+        assert_eq!(cpu.registers.PC, 0x8001);
 
         cpu.reset(&mut bus);
+        assert_eq!(cpu.state(), CpuState::Running);
+        assert_eq!(cpu.registers.PC, 0x8000);
+    }
+
+    #[test]
+    fn test_run_page_cross_cycle() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        let mut bus = BusMock::new();
+        bus.write(0x8000, 0xBD); // LDA $12FF,X
+        bus.write(0x8001, 0xFF);
+        bus.write(0x8002, 0x12);
+        bus.write(0x8003, 0x9D); // STA $12FF,X
+        bus.write(0x8004, 0xFF);
+        bus.write(0x8005, 0x12);
+        bus.write(0x8006, 0xD0); // BNE -8
+        bus.write(0x8007, 0xF8);
+
+        cpu.registers.PC = 0x8000;
+        cpu.registers.X = 0x01;
+        cpu.registers.A = 0x01;
+        assert_eq!(cpu.run(&mut bus), Ok(5));
+        assert_eq!(cpu.run(&mut bus), Ok(5));
+        // a taken branch in the same page
+        cpu.registers.P.zero = false;
+        assert_eq!(cpu.run(&mut bus), Ok(3));
         assert_eq!(cpu.registers.PC, 0x8000);
     }
-}
\ No newline at end of file
+
+    fn run_cycle(opecode: u8, index: u8, flag: bool, timing: Timing) -> usize {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, timing);
+        let mut bus = BusMock::new();
+        bus.write(0x8000, opecode);
+        bus.write(0x8001, 0x80); // zero page address, or branch to another page
+        bus.write(0x8002, 0x12);
+        bus.write(0x0080, 0xF0); // indirect address
+        bus.write(0x0081, 0x12);
+
+        cpu.registers.PC = 0x8000;
+        cpu.registers.X = index;
+        cpu.registers.Y = index;
+        cpu.registers.P.carry = flag;
+        cpu.registers.P.zero = flag;
+        cpu.registers.P.negative = flag;
+        cpu.registers.P.overflow = flag;
+        cpu.run(&mut bus).unwrap()
+    }
+
+    #[test]
+    fn test_run_cycle_stepped() {
+        // every bus access takes 1 cycle, so the sum must be the same as the opecode table.
+        for opecode in 0x00..0x100 {
+            let opecode = opecode as u8;
+            match opecode::decode(opecode) {
+                Some(rule) if rule.command != Command::KIL => {},
+                _ => continue,
+            }
+
+            // with and without page crossing, branches are taken or not.
+            for &(index, flag) in [(0x00, false), (0x00, true), (0xFF, false), (0xFF, true)].iter() {
+                let instruction = run_cycle(opecode, index, flag, Timing::Instruction);
+                let cycle_stepped = run_cycle(opecode, index, flag, Timing::CycleStepped);
+                assert_eq!(instruction, cycle_stepped, "opecode: {:02X}", opecode);
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_cycle_stepped_read_modify_write() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::CycleStepped);
+        let mut bus = BusMock::new();
+        bus.write(0x8000, 0xE6); // INC $10
+        bus.write(0x8001, 0x10);
+        bus.write(0x0010, 0x41);
+        bus.writes.clear();
+
+        cpu.registers.PC = 0x8000;
+        assert_eq!(cpu.run(&mut bus), Ok(5));
+        // the unmodified data is written before the modified one.
+        assert_eq!(bus.writes, vec![(0x0010, 0x41), (0x0010, 0x42)]);
+
+        // the fast path doesn't perform the dummy write.
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        bus.writes.clear();
+        cpu.registers.PC = 0x8000;
+        assert_eq!(cpu.run(&mut bus), Ok(5));
+        assert_eq!(bus.writes, vec![(0x0010, 0x43)]);
+    }
+
+    #[test]
+    fn test_run_cycle_stepped_interrupt() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::CycleStepped);
+        let mut bus = BusMock::new();
+        bus.write(0xFFFA, 0x00);
+        bus.write(0xFFFB, 0x90);
+
+        cpu.registers.PC = 0x8000;
+        cpu.set_nmi(true);
+        assert_eq!(cpu.run(&mut bus), Ok(7));
+        assert_eq!(cpu.registers.PC, 0x9000);
+    }
+
+    struct TracerMock {
+        lines: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Tracer for TracerMock {
+        fn trace(&mut self, line: &str) {
+            self.lines.borrow_mut().push(String::from(line));
+        }
+    }
+
+    #[test]
+    fn test_run_tracer() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        let mut bus = BusMock::new();
+        bus.write(0xFFFC, 0x00);
+        bus.write(0xFFFD, 0xC0);
+        bus.write(0xC000, 0xA9); // LDA #$10
+        bus.write(0xC001, 0x10);
+        bus.write(0xC002, 0xEA); // NOP
+
+        let lines = Rc::new(RefCell::new(vec![]));
+        cpu.set_tracer(Some(Box::new(TracerMock { lines: lines.clone() })));
+        cpu.reset(&mut bus);
+        cpu.run(&mut bus).unwrap();
+        cpu.run(&mut bus).unwrap();
+        assert_eq!(cpu.cycle(), 11);
+
+        assert_eq!(*lines.borrow(), vec![
+            "C000  A9 10     LDA #$10                        A:00 X:00 Y:00 P:24 SP:FD PPU:  0,  0 CYC:7",
+            "C002  EA        NOP                             A:10 X:00 Y:00 P:24 SP:FD PPU:  0,  0 CYC:9",
+        ]);
+    }
+
+    struct LabelsMock;
+
+    impl Labels for LabelsMock {
+        fn label(&self, addr: u16) -> Option<&str> {
+            if addr == 0xC000 { Some("reset") } else { None }
+        }
+    }
+
+    #[test]
+    fn test_run_tracer_with_labels() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        let mut bus = BusMock::new();
+        bus.write(0xC000, 0xD0); // BNE reset
+        bus.write(0xC001, 0xFE);
+
+        let lines = Rc::new(RefCell::new(vec![]));
+        cpu.set_tracer(Some(Box::new(TracerMock { lines: lines.clone() })));
+        cpu.set_labels(Some(Rc::new(LabelsMock)));
+        cpu.registers.PC = 0xC000;
+        cpu.run(&mut bus).unwrap();
+
+        assert_eq!(*lines.borrow(), vec![
+            "reset:",
+            "C000  D0 FE     BNE reset                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0,  0 CYC:0",
+        ]);
+    }
+
+    #[test]
+    fn test_run_profiler() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        let mut bus = BusMock::new();
+        bus.write(0xFFFA, 0x00);
+        bus.write(0xFFFB, 0x90);
+        bus.write(0x8000, 0x20); // JSR $8010
+        bus.write(0x8001, 0x10);
+        bus.write(0x8002, 0x80);
+        bus.write(0x8010, 0x60); // RTS
+        bus.write(0x9000, 0x40); // RTI
+
+        cpu.set_profiler(Some(Profiler::new()));
+        cpu.registers.PC = 0x8000;
+        cpu.run(&mut bus).unwrap();
+        cpu.set_nmi(true);
+        cpu.run(&mut bus).unwrap();
+        cpu.run(&mut bus).unwrap();
+        cpu.run(&mut bus).unwrap();
+        cpu.profiler_mut().unwrap().end_frame();
+
+        let stats = |calls, inclusive, exclusive| RoutineStats { calls: calls, inclusive: inclusive, exclusive: exclusive };
+        assert_eq!(cpu.profiler().unwrap().last_frame(), vec![
+            (0x9000, stats(1, 13, 13)),
+            (0x8000, stats(1, 25, 6)),
+            (0x8010, stats(1, 19, 6)),
+        ]);
+    }
+
+    #[test]
+    fn test_run_unknown_opecode() {
+        let mut cpu = Cpu::new(Variant::Ricoh2A03, Timing::Instruction);
+        let mut bus = BusMock::new();
+        bus.write(0x8000, 0x8B); // XAA isn't supported
+
+        cpu.registers.PC = 0x8000;
+        assert_eq!(cpu.run(&mut bus), Err((CpuError::UnknownOpecode { pc: 0x8000, opecode: 0x8B }, 1)));
+        assert_eq!(cpu.registers.PC, 0x8001);
+        assert_eq!(cpu.cycle(), 1);
+    }
+}
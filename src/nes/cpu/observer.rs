@@ -0,0 +1,23 @@
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AccessKind {
+    Read,
+    Write,
+    /// The read of the opecode or the operand of the instruction at PC.
+    Fetch,
+}
+
+/// An access of the CPU to the bus.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Access {
+    pub addr: u16,
+    pub data: u8,
+    pub kind: AccessKind,
+    /// The CPU cycle since the reset. The instruction-stepped CPU gives the cycle where the instruction starts.
+    pub cycle: usize,
+}
+
+/// Receives every access which the CPU performs through `Bus`.
+/// Reads without side effects by the debugging tools aren't observed.
+pub trait BusObserver {
+    fn observe(&mut self, access: &Access);
+}
@@ -1,9 +1,11 @@
-use std::collections::HashMap;
-
 pub struct Opecode {
     pub command: Command,
     pub mode: AddressingMode,
+    /// Base cycle without the page crossing and branch penalties.
     pub cycle: usize,
+    /// Byte length including the opecode.
+    pub length: u16,
+    pub official: bool,
 }
 
 
@@ -24,67 +26,513 @@ pub enum AddressingMode {
     PostIndexedIndirect,
 }
 
-#[derive(Debug)]
+impl AddressingMode {
+    /// Indexed addressing which can cross a page while adding the index.
+    pub fn is_indexed(&self) -> bool {
+        match *self {
+            AddressingMode::AbsoluteX | AddressingMode::AbsoluteY |
+            AddressingMode::PostIndexedIndirect => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
 pub enum Command {
+    ADC,
+    AND,
+    ASL,
+    BCC,
+    BCS,
+    BEQ,
+    BIT,
+    BMI,
     BNE,
+    BPL,
+    BRK,
+    BVC,
+    BVS,
+    CLC,
+    CLD,
+    CLI,
+    CLV,
+    CMP,
+    CPX,
+    CPY,
+    DEC,
+    DEX,
     DEY,
+    EOR,
+    INC,
     INX,
+    INY,
     JMP,
+    JSR,
     LDA,
     LDX,
     LDY,
+    LSR,
+    NOP,
+    ORA,
+    PHA,
+    PHP,
+    PLA,
+    PLP,
+    ROL,
+    ROR,
+    RTI,
+    RTS,
+    SBC,
+    SEC,
+    SED,
     SEI,
     STA,
+    STX,
+    STY,
+    TAX,
+    TAY,
+    TSX,
+    TXA,
     TXS,
+    TYA,
+
+    // unofficial opecodes
+    #[cfg(feature = "illegal-opcodes")]
+    ALR,
+    #[cfg(feature = "illegal-opcodes")]
+    ANC,
+    #[cfg(feature = "illegal-opcodes")]
+    ARR,
+    #[cfg(feature = "illegal-opcodes")]
+    AXS,
+    #[cfg(feature = "illegal-opcodes")]
+    DCP,
+    #[cfg(feature = "illegal-opcodes")]
+    ISC,
+    #[cfg(feature = "illegal-opcodes")]
+    LAX,
+    #[cfg(feature = "illegal-opcodes")]
+    RLA,
+    #[cfg(feature = "illegal-opcodes")]
+    RRA,
+    #[cfg(feature = "illegal-opcodes")]
+    SAX,
+    #[cfg(feature = "illegal-opcodes")]
+    SLO,
+    #[cfg(feature = "illegal-opcodes")]
+    SRE,
+    KIL,
+}
+
+impl Command {
+    /// Read instructions take 1 more cycle when indexed addressing crosses a page.
+    /// Store and read-modify-write instructions always take the longer cycle.
+    pub fn has_page_cross_penalty(&self) -> bool {
+        match *self {
+            Command::ADC | Command::AND | Command::CMP | Command::EOR |
+            Command::LDA | Command::LDX | Command::LDY | Command::NOP |
+            Command::ORA | Command::SBC => true,
+            #[cfg(feature = "illegal-opcodes")]
+            Command::LAX => true,
+            _ => false,
+        }
+    }
+}
+
+macro_rules! official {
+    ($command:ident, $mode:ident, $cycle:expr, $length:expr) => {
+        Some(Opecode { command: Command::$command, mode: AddressingMode::$mode, cycle: $cycle, length: $length, official: true })
+    };
 }
 
-lazy_static! {
-    pub static ref OPECODE_MAP: HashMap<u8, Opecode> = {
-        let cycles: Vec<usize> =
-            vec![7, 6, 2, 8, 3, 3, 5, 5, 3, 2, 2, 2, 4, 4, 6, 6, 2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7,
-                 4, 4, 6, 7, 6, 6, 2, 8, 3, 3, 5, 5, 4, 2, 2, 2, 4, 4, 6, 6, 2, 5, 2, 8, 4, 4, 6, 6,
-                 2, 4, 2, 7, 4, 4, 6, 7, 6, 6, 2, 8, 3, 3, 5, 5, 3, 2, 2, 2, 3, 4, 6, 6, 2, 5, 2, 8,
-                 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 6, 7, 6, 6, 2, 8, 3, 3, 5, 5, 4, 2, 2, 2, 5, 4, 6, 6,
-                 2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 6, 7, 2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2,
-                 4, 4, 4, 4, 2, 6, 2, 6, 4, 4, 4, 4, 2, 4, 2, 5, 5, 4, 5, 5, 2, 6, 2, 6, 3, 3, 3, 3,
-                 2, 2, 2, 2, 4, 4, 4, 4, 2, 5, 2, 5, 4, 4, 4, 4, 2, 4, 2, 4, 4, 4, 4, 4, 2, 6, 2, 8,
-                 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6, 2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
-                 2, 6, 3, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6, 2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7,
-                 4, 4, 7, 7];
-
-        let mut m = HashMap::new();
-        m.insert(0xA9, Opecode { command: Command::LDA, mode: AddressingMode::Immediate, cycle: cycles[0xA9] });
-        m.insert(0xA5, Opecode { command: Command::LDA, mode: AddressingMode::ZeroPage, cycle: cycles[0xA5] });
-        m.insert(0xAD, Opecode { command: Command::LDA, mode: AddressingMode::Absolute, cycle: cycles[0xAD] });
-        m.insert(0xB5, Opecode { command: Command::LDA, mode: AddressingMode::ZeroPageX, cycle: cycles[0xB5] });
-        m.insert(0xBD, Opecode { command: Command::LDA, mode: AddressingMode::AbsoluteX, cycle: cycles[0xBD] });
-        m.insert(0xB9, Opecode { command: Command::LDA, mode: AddressingMode::AbsoluteY, cycle: cycles[0xB9] });
-        m.insert(0xA1, Opecode { command: Command::LDA, mode: AddressingMode::PreIndexedIndirect, cycle: cycles[0xA1] });
-        m.insert(0xB1, Opecode { command: Command::LDA, mode: AddressingMode::PostIndexedIndirect, cycle: cycles[0xB1] });
-        m.insert(0xA2, Opecode { command: Command::LDX, mode: AddressingMode::Immediate, cycle: cycles[0xA2] });
-        m.insert(0xA6, Opecode { command: Command::LDX, mode: AddressingMode::ZeroPage, cycle: cycles[0xA6] });
-        m.insert(0xAE, Opecode { command: Command::LDX, mode: AddressingMode::Absolute, cycle: cycles[0xAE] });
-        m.insert(0xB6, Opecode { command: Command::LDX, mode: AddressingMode::ZeroPageY, cycle: cycles[0xB6] });
-        m.insert(0xBE, Opecode { command: Command::LDX, mode: AddressingMode::AbsoluteY, cycle: cycles[0xBE] });
-        m.insert(0xA0, Opecode { command: Command::LDY, mode: AddressingMode::Immediate, cycle: cycles[0xA0] });
-        m.insert(0xA4, Opecode { command: Command::LDY, mode: AddressingMode::ZeroPage, cycle: cycles[0xA4] });
-        m.insert(0xAC, Opecode { command: Command::LDY, mode: AddressingMode::Absolute, cycle: cycles[0xAC] });
-        m.insert(0xB4, Opecode { command: Command::LDY, mode: AddressingMode::ZeroPageX, cycle: cycles[0xB4] });
-        m.insert(0xBC, Opecode { command: Command::LDY, mode: AddressingMode::AbsoluteX, cycle: cycles[0xBC] });
-        m.insert(0x85, Opecode { command: Command::STA, mode: AddressingMode::ZeroPage, cycle: cycles[0x85] });
-        m.insert(0x8D, Opecode { command: Command::STA, mode: AddressingMode::Absolute, cycle: cycles[0x8D] });
-        m.insert(0x95, Opecode { command: Command::STA, mode: AddressingMode::ZeroPageX, cycle: cycles[0x95] });
-        m.insert(0x9D, Opecode { command: Command::STA, mode: AddressingMode::AbsoluteX, cycle: cycles[0x9D] });
-        m.insert(0x99, Opecode { command: Command::STA, mode: AddressingMode::AbsoluteY, cycle: cycles[0x99] });
-        m.insert(0x81, Opecode { command: Command::STA, mode: AddressingMode::PreIndexedIndirect, cycle: cycles[0x81] });
-        m.insert(0x91, Opecode { command: Command::STA, mode: AddressingMode::PostIndexedIndirect, cycle: cycles[0x91] });
-        m.insert(0x9A, Opecode { command: Command::TXS, mode: AddressingMode::Implied, cycle: cycles[0x9A] });
-        m.insert(0xE8, Opecode { command: Command::INX, mode: AddressingMode::Implied, cycle: cycles[0xE8] });
-        m.insert(0x88, Opecode { command: Command::DEY, mode: AddressingMode::Implied, cycle: cycles[0x88] });
-        m.insert(0x78, Opecode { command: Command::SEI, mode: AddressingMode::Implied, cycle: cycles[0x78] });
-        m.insert(0x4C, Opecode { command: Command::JMP, mode: AddressingMode::Absolute, cycle: cycles[0x4C] });
-        m.insert(0x6C, Opecode { command: Command::JMP, mode: AddressingMode::IndirectAbsolute, cycle: cycles[0x6C] });
-        m.insert(0xD0, Opecode { command: Command::BNE, mode: AddressingMode::Relative, cycle: cycles[0xD0] });
-        m
+#[cfg(feature = "illegal-opcodes")]
+macro_rules! unofficial {
+    ($command:ident, $mode:ident, $cycle:expr, $length:expr) => {
+        Some(Opecode { command: Command::$command, mode: AddressingMode::$mode, cycle: $cycle, length: $length, official: false })
     };
-}
\ No newline at end of file
+}
+
+// unofficial opecodes are not decoded without the feature.
+#[cfg(not(feature = "illegal-opcodes"))]
+macro_rules! unofficial {
+    ($command:ident, $mode:ident, $cycle:expr, $length:expr) => { None };
+}
+
+// KIL/JAM is always decoded, so that the CPU jams instead of failing.
+macro_rules! jam {
+    () => {
+        Some(Opecode { command: Command::KIL, mode: AddressingMode::Implied, cycle: 2, length: 1, official: false })
+    };
+}
+
+/// The decode table indexed by opecode. It is built at compile time.
+/// Unstable opecodes(XAA, AHX, TAS, SHX, SHY, LAS) are not supported.
+pub static OPECODE_TABLE: [Option<Opecode>; 256] = [
+    /* 0x00 */ official!(BRK, Implied, 7, 1),
+    /* 0x01 */ official!(ORA, PreIndexedIndirect, 6, 2),
+    /* 0x02 */ jam!(),
+    /* 0x03 */ unofficial!(SLO, PreIndexedIndirect, 8, 2),
+    /* 0x04 */ unofficial!(NOP, ZeroPage, 3, 2),
+    /* 0x05 */ official!(ORA, ZeroPage, 3, 2),
+    /* 0x06 */ official!(ASL, ZeroPage, 5, 2),
+    /* 0x07 */ unofficial!(SLO, ZeroPage, 5, 2),
+    /* 0x08 */ official!(PHP, Implied, 3, 1),
+    /* 0x09 */ official!(ORA, Immediate, 2, 2),
+    /* 0x0A */ official!(ASL, Accumulator, 2, 1),
+    /* 0x0B */ unofficial!(ANC, Immediate, 2, 2),
+    /* 0x0C */ unofficial!(NOP, Absolute, 4, 3),
+    /* 0x0D */ official!(ORA, Absolute, 4, 3),
+    /* 0x0E */ official!(ASL, Absolute, 6, 3),
+    /* 0x0F */ unofficial!(SLO, Absolute, 6, 3),
+    /* 0x10 */ official!(BPL, Relative, 2, 2),
+    /* 0x11 */ official!(ORA, PostIndexedIndirect, 5, 2),
+    /* 0x12 */ jam!(),
+    /* 0x13 */ unofficial!(SLO, PostIndexedIndirect, 8, 2),
+    /* 0x14 */ unofficial!(NOP, ZeroPageX, 4, 2),
+    /* 0x15 */ official!(ORA, ZeroPageX, 4, 2),
+    /* 0x16 */ official!(ASL, ZeroPageX, 6, 2),
+    /* 0x17 */ unofficial!(SLO, ZeroPageX, 6, 2),
+    /* 0x18 */ official!(CLC, Implied, 2, 1),
+    /* 0x19 */ official!(ORA, AbsoluteY, 4, 3),
+    /* 0x1A */ unofficial!(NOP, Implied, 2, 1),
+    /* 0x1B */ unofficial!(SLO, AbsoluteY, 7, 3),
+    /* 0x1C */ unofficial!(NOP, AbsoluteX, 4, 3),
+    /* 0x1D */ official!(ORA, AbsoluteX, 4, 3),
+    /* 0x1E */ official!(ASL, AbsoluteX, 7, 3),
+    /* 0x1F */ unofficial!(SLO, AbsoluteX, 7, 3),
+    /* 0x20 */ official!(JSR, Absolute, 6, 3),
+    /* 0x21 */ official!(AND, PreIndexedIndirect, 6, 2),
+    /* 0x22 */ jam!(),
+    /* 0x23 */ unofficial!(RLA, PreIndexedIndirect, 8, 2),
+    /* 0x24 */ official!(BIT, ZeroPage, 3, 2),
+    /* 0x25 */ official!(AND, ZeroPage, 3, 2),
+    /* 0x26 */ official!(ROL, ZeroPage, 5, 2),
+    /* 0x27 */ unofficial!(RLA, ZeroPage, 5, 2),
+    /* 0x28 */ official!(PLP, Implied, 4, 1),
+    /* 0x29 */ official!(AND, Immediate, 2, 2),
+    /* 0x2A */ official!(ROL, Accumulator, 2, 1),
+    /* 0x2B */ unofficial!(ANC, Immediate, 2, 2),
+    /* 0x2C */ official!(BIT, Absolute, 4, 3),
+    /* 0x2D */ official!(AND, Absolute, 4, 3),
+    /* 0x2E */ official!(ROL, Absolute, 6, 3),
+    /* 0x2F */ unofficial!(RLA, Absolute, 6, 3),
+    /* 0x30 */ official!(BMI, Relative, 2, 2),
+    /* 0x31 */ official!(AND, PostIndexedIndirect, 5, 2),
+    /* 0x32 */ jam!(),
+    /* 0x33 */ unofficial!(RLA, PostIndexedIndirect, 8, 2),
+    /* 0x34 */ unofficial!(NOP, ZeroPageX, 4, 2),
+    /* 0x35 */ official!(AND, ZeroPageX, 4, 2),
+    /* 0x36 */ official!(ROL, ZeroPageX, 6, 2),
+    /* 0x37 */ unofficial!(RLA, ZeroPageX, 6, 2),
+    /* 0x38 */ official!(SEC, Implied, 2, 1),
+    /* 0x39 */ official!(AND, AbsoluteY, 4, 3),
+    /* 0x3A */ unofficial!(NOP, Implied, 2, 1),
+    /* 0x3B */ unofficial!(RLA, AbsoluteY, 7, 3),
+    /* 0x3C */ unofficial!(NOP, AbsoluteX, 4, 3),
+    /* 0x3D */ official!(AND, AbsoluteX, 4, 3),
+    /* 0x3E */ official!(ROL, AbsoluteX, 7, 3),
+    /* 0x3F */ unofficial!(RLA, AbsoluteX, 7, 3),
+    /* 0x40 */ official!(RTI, Implied, 6, 1),
+    /* 0x41 */ official!(EOR, PreIndexedIndirect, 6, 2),
+    /* 0x42 */ jam!(),
+    /* 0x43 */ unofficial!(SRE, PreIndexedIndirect, 8, 2),
+    /* 0x44 */ unofficial!(NOP, ZeroPage, 3, 2),
+    /* 0x45 */ official!(EOR, ZeroPage, 3, 2),
+    /* 0x46 */ official!(LSR, ZeroPage, 5, 2),
+    /* 0x47 */ unofficial!(SRE, ZeroPage, 5, 2),
+    /* 0x48 */ official!(PHA, Implied, 3, 1),
+    /* 0x49 */ official!(EOR, Immediate, 2, 2),
+    /* 0x4A */ official!(LSR, Accumulator, 2, 1),
+    /* 0x4B */ unofficial!(ALR, Immediate, 2, 2),
+    /* 0x4C */ official!(JMP, Absolute, 3, 3),
+    /* 0x4D */ official!(EOR, Absolute, 4, 3),
+    /* 0x4E */ official!(LSR, Absolute, 6, 3),
+    /* 0x4F */ unofficial!(SRE, Absolute, 6, 3),
+    /* 0x50 */ official!(BVC, Relative, 2, 2),
+    /* 0x51 */ official!(EOR, PostIndexedIndirect, 5, 2),
+    /* 0x52 */ jam!(),
+    /* 0x53 */ unofficial!(SRE, PostIndexedIndirect, 8, 2),
+    /* 0x54 */ unofficial!(NOP, ZeroPageX, 4, 2),
+    /* 0x55 */ official!(EOR, ZeroPageX, 4, 2),
+    /* 0x56 */ official!(LSR, ZeroPageX, 6, 2),
+    /* 0x57 */ unofficial!(SRE, ZeroPageX, 6, 2),
+    /* 0x58 */ official!(CLI, Implied, 2, 1),
+    /* 0x59 */ official!(EOR, AbsoluteY, 4, 3),
+    /* 0x5A */ unofficial!(NOP, Implied, 2, 1),
+    /* 0x5B */ unofficial!(SRE, AbsoluteY, 7, 3),
+    /* 0x5C */ unofficial!(NOP, AbsoluteX, 4, 3),
+    /* 0x5D */ official!(EOR, AbsoluteX, 4, 3),
+    /* 0x5E */ official!(LSR, AbsoluteX, 7, 3),
+    /* 0x5F */ unofficial!(SRE, AbsoluteX, 7, 3),
+    /* 0x60 */ official!(RTS, Implied, 6, 1),
+    /* 0x61 */ official!(ADC, PreIndexedIndirect, 6, 2),
+    /* 0x62 */ jam!(),
+    /* 0x63 */ unofficial!(RRA, PreIndexedIndirect, 8, 2),
+    /* 0x64 */ unofficial!(NOP, ZeroPage, 3, 2),
+    /* 0x65 */ official!(ADC, ZeroPage, 3, 2),
+    /* 0x66 */ official!(ROR, ZeroPage, 5, 2),
+    /* 0x67 */ unofficial!(RRA, ZeroPage, 5, 2),
+    /* 0x68 */ official!(PLA, Implied, 4, 1),
+    /* 0x69 */ official!(ADC, Immediate, 2, 2),
+    /* 0x6A */ official!(ROR, Accumulator, 2, 1),
+    /* 0x6B */ unofficial!(ARR, Immediate, 2, 2),
+    /* 0x6C */ official!(JMP, IndirectAbsolute, 5, 3),
+    /* 0x6D */ official!(ADC, Absolute, 4, 3),
+    /* 0x6E */ official!(ROR, Absolute, 6, 3),
+    /* 0x6F */ unofficial!(RRA, Absolute, 6, 3),
+    /* 0x70 */ official!(BVS, Relative, 2, 2),
+    /* 0x71 */ official!(ADC, PostIndexedIndirect, 5, 2),
+    /* 0x72 */ jam!(),
+    /* 0x73 */ unofficial!(RRA, PostIndexedIndirect, 8, 2),
+    /* 0x74 */ unofficial!(NOP, ZeroPageX, 4, 2),
+    /* 0x75 */ official!(ADC, ZeroPageX, 4, 2),
+    /* 0x76 */ official!(ROR, ZeroPageX, 6, 2),
+    /* 0x77 */ unofficial!(RRA, ZeroPageX, 6, 2),
+    /* 0x78 */ official!(SEI, Implied, 2, 1),
+    /* 0x79 */ official!(ADC, AbsoluteY, 4, 3),
+    /* 0x7A */ unofficial!(NOP, Implied, 2, 1),
+    /* 0x7B */ unofficial!(RRA, AbsoluteY, 7, 3),
+    /* 0x7C */ unofficial!(NOP, AbsoluteX, 4, 3),
+    /* 0x7D */ official!(ADC, AbsoluteX, 4, 3),
+    /* 0x7E */ official!(ROR, AbsoluteX, 7, 3),
+    /* 0x7F */ unofficial!(RRA, AbsoluteX, 7, 3),
+    /* 0x80 */ unofficial!(NOP, Immediate, 2, 2),
+    /* 0x81 */ official!(STA, PreIndexedIndirect, 6, 2),
+    /* 0x82 */ unofficial!(NOP, Immediate, 2, 2),
+    /* 0x83 */ unofficial!(SAX, PreIndexedIndirect, 6, 2),
+    /* 0x84 */ official!(STY, ZeroPage, 3, 2),
+    /* 0x85 */ official!(STA, ZeroPage, 3, 2),
+    /* 0x86 */ official!(STX, ZeroPage, 3, 2),
+    /* 0x87 */ unofficial!(SAX, ZeroPage, 3, 2),
+    /* 0x88 */ official!(DEY, Implied, 2, 1),
+    /* 0x89 */ unofficial!(NOP, Immediate, 2, 2),
+    /* 0x8A */ official!(TXA, Implied, 2, 1),
+    /* 0x8B */ None,
+    /* 0x8C */ official!(STY, Absolute, 4, 3),
+    /* 0x8D */ official!(STA, Absolute, 4, 3),
+    /* 0x8E */ official!(STX, Absolute, 4, 3),
+    /* 0x8F */ unofficial!(SAX, Absolute, 4, 3),
+    /* 0x90 */ official!(BCC, Relative, 2, 2),
+    /* 0x91 */ official!(STA, PostIndexedIndirect, 6, 2),
+    /* 0x92 */ jam!(),
+    /* 0x93 */ None,
+    /* 0x94 */ official!(STY, ZeroPageX, 4, 2),
+    /* 0x95 */ official!(STA, ZeroPageX, 4, 2),
+    /* 0x96 */ official!(STX, ZeroPageY, 4, 2),
+    /* 0x97 */ unofficial!(SAX, ZeroPageY, 4, 2),
+    /* 0x98 */ official!(TYA, Implied, 2, 1),
+    /* 0x99 */ official!(STA, AbsoluteY, 5, 3),
+    /* 0x9A */ official!(TXS, Implied, 2, 1),
+    /* 0x9B */ None,
+    /* 0x9C */ None,
+    /* 0x9D */ official!(STA, AbsoluteX, 5, 3),
+    /* 0x9E */ None,
+    /* 0x9F */ None,
+    /* 0xA0 */ official!(LDY, Immediate, 2, 2),
+    /* 0xA1 */ official!(LDA, PreIndexedIndirect, 6, 2),
+    /* 0xA2 */ official!(LDX, Immediate, 2, 2),
+    /* 0xA3 */ unofficial!(LAX, PreIndexedIndirect, 6, 2),
+    /* 0xA4 */ official!(LDY, ZeroPage, 3, 2),
+    /* 0xA5 */ official!(LDA, ZeroPage, 3, 2),
+    /* 0xA6 */ official!(LDX, ZeroPage, 3, 2),
+    /* 0xA7 */ unofficial!(LAX, ZeroPage, 3, 2),
+    /* 0xA8 */ official!(TAY, Implied, 2, 1),
+    /* 0xA9 */ official!(LDA, Immediate, 2, 2),
+    /* 0xAA */ official!(TAX, Implied, 2, 1),
+    /* 0xAB */ None,
+    /* 0xAC */ official!(LDY, Absolute, 4, 3),
+    /* 0xAD */ official!(LDA, Absolute, 4, 3),
+    /* 0xAE */ official!(LDX, Absolute, 4, 3),
+    /* 0xAF */ unofficial!(LAX, Absolute, 4, 3),
+    /* 0xB0 */ official!(BCS, Relative, 2, 2),
+    /* 0xB1 */ official!(LDA, PostIndexedIndirect, 5, 2),
+    /* 0xB2 */ jam!(),
+    /* 0xB3 */ unofficial!(LAX, PostIndexedIndirect, 5, 2),
+    /* 0xB4 */ official!(LDY, ZeroPageX, 4, 2),
+    /* 0xB5 */ official!(LDA, ZeroPageX, 4, 2),
+    /* 0xB6 */ official!(LDX, ZeroPageY, 4, 2),
+    /* 0xB7 */ unofficial!(LAX, ZeroPageY, 4, 2),
+    /* 0xB8 */ official!(CLV, Implied, 2, 1),
+    /* 0xB9 */ official!(LDA, AbsoluteY, 4, 3),
+    /* 0xBA */ official!(TSX, Implied, 2, 1),
+    /* 0xBB */ None,
+    /* 0xBC */ official!(LDY, AbsoluteX, 4, 3),
+    /* 0xBD */ official!(LDA, AbsoluteX, 4, 3),
+    /* 0xBE */ official!(LDX, AbsoluteY, 4, 3),
+    /* 0xBF */ unofficial!(LAX, AbsoluteY, 4, 3),
+    /* 0xC0 */ official!(CPY, Immediate, 2, 2),
+    /* 0xC1 */ official!(CMP, PreIndexedIndirect, 6, 2),
+    /* 0xC2 */ unofficial!(NOP, Immediate, 2, 2),
+    /* 0xC3 */ unofficial!(DCP, PreIndexedIndirect, 8, 2),
+    /* 0xC4 */ official!(CPY, ZeroPage, 3, 2),
+    /* 0xC5 */ official!(CMP, ZeroPage, 3, 2),
+    /* 0xC6 */ official!(DEC, ZeroPage, 5, 2),
+    /* 0xC7 */ unofficial!(DCP, ZeroPage, 5, 2),
+    /* 0xC8 */ official!(INY, Implied, 2, 1),
+    /* 0xC9 */ official!(CMP, Immediate, 2, 2),
+    /* 0xCA */ official!(DEX, Implied, 2, 1),
+    /* 0xCB */ unofficial!(AXS, Immediate, 2, 2),
+    /* 0xCC */ official!(CPY, Absolute, 4, 3),
+    /* 0xCD */ official!(CMP, Absolute, 4, 3),
+    /* 0xCE */ official!(DEC, Absolute, 6, 3),
+    /* 0xCF */ unofficial!(DCP, Absolute, 6, 3),
+    /* 0xD0 */ official!(BNE, Relative, 2, 2),
+    /* 0xD1 */ official!(CMP, PostIndexedIndirect, 5, 2),
+    /* 0xD2 */ jam!(),
+    /* 0xD3 */ unofficial!(DCP, PostIndexedIndirect, 8, 2),
+    /* 0xD4 */ unofficial!(NOP, ZeroPageX, 4, 2),
+    /* 0xD5 */ official!(CMP, ZeroPageX, 4, 2),
+    /* 0xD6 */ official!(DEC, ZeroPageX, 6, 2),
+    /* 0xD7 */ unofficial!(DCP, ZeroPageX, 6, 2),
+    /* 0xD8 */ official!(CLD, Implied, 2, 1),
+    /* 0xD9 */ official!(CMP, AbsoluteY, 4, 3),
+    /* 0xDA */ unofficial!(NOP, Implied, 2, 1),
+    /* 0xDB */ unofficial!(DCP, AbsoluteY, 7, 3),
+    /* 0xDC */ unofficial!(NOP, AbsoluteX, 4, 3),
+    /* 0xDD */ official!(CMP, AbsoluteX, 4, 3),
+    /* 0xDE */ official!(DEC, AbsoluteX, 7, 3),
+    /* 0xDF */ unofficial!(DCP, AbsoluteX, 7, 3),
+    /* 0xE0 */ official!(CPX, Immediate, 2, 2),
+    /* 0xE1 */ official!(SBC, PreIndexedIndirect, 6, 2),
+    /* 0xE2 */ unofficial!(NOP, Immediate, 2, 2),
+    /* 0xE3 */ unofficial!(ISC, PreIndexedIndirect, 8, 2),
+    /* 0xE4 */ official!(CPX, ZeroPage, 3, 2),
+    /* 0xE5 */ official!(SBC, ZeroPage, 3, 2),
+    /* 0xE6 */ official!(INC, ZeroPage, 5, 2),
+    /* 0xE7 */ unofficial!(ISC, ZeroPage, 5, 2),
+    /* 0xE8 */ official!(INX, Implied, 2, 1),
+    /* 0xE9 */ official!(SBC, Immediate, 2, 2),
+    /* 0xEA */ official!(NOP, Implied, 2, 1),
+    /* 0xEB */ unofficial!(SBC, Immediate, 2, 2),
+    /* 0xEC */ official!(CPX, Absolute, 4, 3),
+    /* 0xED */ official!(SBC, Absolute, 4, 3),
+    /* 0xEE */ official!(INC, Absolute, 6, 3),
+    /* 0xEF */ unofficial!(ISC, Absolute, 6, 3),
+    /* 0xF0 */ official!(BEQ, Relative, 2, 2),
+    /* 0xF1 */ official!(SBC, PostIndexedIndirect, 5, 2),
+    /* 0xF2 */ jam!(),
+    /* 0xF3 */ unofficial!(ISC, PostIndexedIndirect, 8, 2),
+    /* 0xF4 */ unofficial!(NOP, ZeroPageX, 4, 2),
+    /* 0xF5 */ official!(SBC, ZeroPageX, 4, 2),
+    /* 0xF6 */ official!(INC, ZeroPageX, 6, 2),
+    /* 0xF7 */ unofficial!(ISC, ZeroPageX, 6, 2),
+    /* 0xF8 */ official!(SED, Implied, 2, 1),
+    /* 0xF9 */ official!(SBC, AbsoluteY, 4, 3),
+    /* 0xFA */ unofficial!(NOP, Implied, 2, 1),
+    /* 0xFB */ unofficial!(ISC, AbsoluteY, 7, 3),
+    /* 0xFC */ unofficial!(NOP, AbsoluteX, 4, 3),
+    /* 0xFD */ official!(SBC, AbsoluteX, 4, 3),
+    /* 0xFE */ official!(INC, AbsoluteX, 7, 3),
+    /* 0xFF */ unofficial!(ISC, AbsoluteX, 7, 3),
+];
+
+pub fn decode(opecode: u8) -> Option<&'static Opecode> {
+    OPECODE_TABLE[opecode as usize].as_ref()
+}
+
+#[cfg(test)]
+mod opecode_test {
+    use super::*;
+
+    #[test]
+    fn decode_test() {
+        let opecode = decode(0x1E).unwrap();
+        assert_eq!(opecode.command, Command::ASL);
+        assert_eq!(opecode.mode, AddressingMode::AbsoluteX);
+        assert_eq!(opecode.cycle, 7);
+
+        let opecode = decode(0x96).unwrap();
+        assert_eq!(opecode.command, Command::STX);
+        assert_eq!(opecode.mode, AddressingMode::ZeroPageY);
+        assert_eq!(opecode.cycle, 4);
+        assert_eq!(opecode.length, 2);
+        assert_eq!(opecode.official, true);
+    }
+
+    #[test]
+    fn official_opecode_count_test() {
+        let count = OPECODE_TABLE.iter().filter(|rule| match **rule {
+            Some(ref rule) => rule.official,
+            None => false,
+        }).count();
+        assert_eq!(count, 151);
+    }
+
+    #[test]
+    fn kil_opecode_test() {
+        let opecode = decode(0x02).unwrap();
+        assert_eq!(opecode.command, Command::KIL);
+        assert_eq!(opecode.mode, AddressingMode::Implied);
+        assert_eq!(opecode.official, false);
+    }
+
+    #[cfg(not(feature = "illegal-opcodes"))]
+    #[test]
+    fn decode_without_illegal_opecodes_test() {
+        assert!(decode(0xA7).is_none());
+        assert!(decode(0xEB).is_none());
+    }
+
+    #[cfg(feature = "illegal-opcodes")]
+    #[test]
+    fn decode_illegal_opecode_test() {
+        let opecode = decode(0xA7).unwrap();
+        assert_eq!(opecode.command, Command::LAX);
+        assert_eq!(opecode.mode, AddressingMode::ZeroPage);
+        assert_eq!(opecode.cycle, 3);
+
+        assert_eq!(opecode.official, false);
+
+        let opecode = decode(0xE2).unwrap();
+        assert_eq!(opecode.command, Command::NOP);
+        assert_eq!(opecode.cycle, 2);
+    }
+
+    #[test]
+    fn has_page_cross_penalty_test() {
+        assert_eq!(Command::LDA.has_page_cross_penalty(), true);
+        assert_eq!(Command::STA.has_page_cross_penalty(), false);
+        assert_eq!(Command::INC.has_page_cross_penalty(), false);
+    }
+}
+
+#[cfg(test)]
+mod opecode_bench {
+    use super::*;
+    use std::collections::HashMap;
+    use test::{black_box, Bencher};
+
+    #[bench]
+    fn decode_table_bench(b: &mut Bencher) {
+        b.iter(|| {
+            let mut cycle = 0;
+            for opecode in 0..0x100 {
+                if let Some(rule) = decode(black_box(opecode as u8)) {
+                    cycle += rule.cycle;
+                }
+            }
+            cycle
+        });
+    }
+
+    // the lookup which was used before the decode table, for comparison.
+    #[bench]
+    fn decode_hash_map_bench(b: &mut Bencher) {
+        let mut map: HashMap<u8, &Opecode> = HashMap::new();
+        for opecode in 0..0x100 {
+            if let Some(rule) = decode(opecode as u8) {
+                map.insert(opecode as u8, rule);
+            }
+        }
+
+        b.iter(|| {
+            let mut cycle = 0;
+            for opecode in 0..0x100 {
+                if let Some(rule) = map.get(&black_box(opecode as u8)) {
+                    cycle += rule.cycle;
+                }
+            }
+            cycle
+        });
+    }
+}
@@ -0,0 +1,47 @@
+/// The latch of the CPU data bus.
+/// The bus keeps the last driven value, so bits which nobody drives are read as that value.
+#[derive(Clone)]
+pub struct OpenBus {
+    latch: u8,
+}
+
+impl OpenBus {
+    pub fn new() -> Self {
+        Self { latch: 0x00 }
+    }
+
+    pub fn latch(&self) -> u8 {
+        self.latch
+    }
+
+    /// Drives all bits with `data`.
+    pub fn drive(&mut self, data: u8) -> u8 {
+        self.drive_partially(data, 0xFF)
+    }
+
+    /// Drives only the bits of `mask` with `data`, the other bits keep the last value.
+    pub fn drive_partially(&mut self, data: u8, mask: u8) -> u8 {
+        self.latch = (data & mask) | (self.latch & !mask);
+        self.latch
+    }
+}
+
+#[cfg(test)]
+mod open_bus_test {
+    use super::*;
+
+    #[test]
+    fn drive_test() {
+        let mut open_bus = OpenBus::new();
+        assert_eq!(open_bus.drive(0x40), 0x40);
+        assert_eq!(open_bus.latch(), 0x40);
+    }
+
+    #[test]
+    fn drive_partially_test() {
+        let mut open_bus = OpenBus::new();
+        open_bus.drive(0x40);
+        assert_eq!(open_bus.drive_partially(0x01, 0x1F), 0x41);
+        assert_eq!(open_bus.drive_partially(0xFF, 0x00), 0x41);
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+
+use super::disassembler::Labels;
+use super::opecode::{self, Command};
+
+/// The cycles spent by a subroutine, which is identified by its entry address.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct RoutineStats {
+    pub calls: usize,
+    /// Including the subroutines called from it.
+    pub inclusive: usize,
+    /// Only its own instructions.
+    pub exclusive: usize,
+}
+
+/// A running subroutine, `sp` is S after its return address is pushed.
+struct Frame {
+    addr: u16,
+    sp: u8,
+}
+
+/// Attributes the cycles of every instruction to the subroutine which executes it.
+///
+/// Subroutines are entered by JSR and the interrupts, and they are left when S passes their return address,
+/// so the returns by PLA and JMP are tracked, and RTS to an address pushed as a jump table isn't a return.
+/// The routine running when the profiling starts is the root, which is never returned from.
+pub struct Profiler {
+    stack: Vec<Frame>,
+    // the number of the frames of each routine in the stack.
+    running: HashMap<u16, usize>,
+    frame: HashMap<u16, RoutineStats>,
+    last_frame: HashMap<u16, RoutineStats>,
+    total: HashMap<u16, RoutineStats>,
+    frames: usize,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            stack: vec![],
+            running: HashMap::new(),
+            frame: HashMap::new(),
+            last_frame: HashMap::new(),
+            total: HashMap::new(),
+            frames: 0,
+        }
+    }
+
+    /// Records the instruction at `pc` which took `cycle`, `next_pc` and `sp` are PC and S after it.
+    pub fn execute(&mut self, pc: u16, opecode: u8, next_pc: u16, sp: u8, cycle: usize) {
+        self.enter_root(pc, sp);
+
+        match opecode::decode(opecode).map(|rule| &rule.command) {
+            // the call belongs to the caller.
+            Some(&Command::JSR) => {
+                self.spend(cycle);
+                self.call(next_pc, sp);
+            },
+            // BRK is a software interrupt.
+            Some(&Command::BRK) => self.interrupt(pc, next_pc, sp, cycle),
+            // the return belongs to the callee.
+            _ => {
+                self.spend(cycle);
+                self.unwind(sp);
+            },
+        }
+    }
+
+    /// Records the interrupt taken at `pc`, the handler is at `next_pc` and S is `sp` after it.
+    pub fn interrupt(&mut self, pc: u16, next_pc: u16, sp: u8, cycle: usize) {
+        self.enter_root(pc, sp);
+        self.call(next_pc, sp);
+        self.spend(cycle);
+    }
+
+    /// Closes the stats of the current frame, the running subroutines are continued.
+    pub fn end_frame(&mut self) {
+        for (addr, stats) in self.frame.iter() {
+            let total = self.total.entry(*addr).or_insert_with(RoutineStats::default);
+            total.calls += stats.calls;
+            total.inclusive += stats.inclusive;
+            total.exclusive += stats.exclusive;
+        }
+
+        self.last_frame = self.frame.drain().collect();
+        self.frames += 1;
+    }
+
+    /// The number of the closed frames.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The stats of the last closed frame, the hottest routine comes first.
+    pub fn last_frame(&self) -> Vec<(u16, RoutineStats)> {
+        Profiler::hot_spots(&self.last_frame)
+    }
+
+    /// The stats of all closed frames, the hottest routine comes first.
+    pub fn total(&self) -> Vec<(u16, RoutineStats)> {
+        Profiler::hot_spots(&self.total)
+    }
+
+    /// Formats the averages per frame of the closed frames, and the last frame.
+    pub fn report(&self, labels: Option<&dyn Labels>) -> String {
+        let mut lines = vec![
+            format!("{} frames", self.frames),
+            format!("{:<24} {:>10} {:>12} {:>12}", "routine", "calls", "inclusive", "exclusive"),
+        ];
+
+        lines.push(String::from("average per frame:"));
+        Profiler::format_routines(&mut lines, &self.total(), self.frames.max(1), labels);
+        lines.push(String::from("last frame:"));
+        Profiler::format_routines(&mut lines, &self.last_frame(), 1, labels);
+
+        lines.join("\n")
+    }
+
+    fn format_routines(lines: &mut Vec<String>, routines: &[(u16, RoutineStats)], frames: usize, labels: Option<&dyn Labels>) {
+        for &(addr, stats) in routines {
+            let name = match labels.and_then(|labels| labels.label(addr)) {
+                Some(label) => format!("{} (${:04X})", label, addr),
+                None => format!("${:04X}", addr),
+            };
+            lines.push(format!(
+                "{:<24} {:>10.1} {:>12.1} {:>12.1}",
+                name,
+                stats.calls as f64 / frames as f64,
+                stats.inclusive as f64 / frames as f64,
+                stats.exclusive as f64 / frames as f64,
+            ));
+        }
+    }
+
+    fn hot_spots(routines: &HashMap<u16, RoutineStats>) -> Vec<(u16, RoutineStats)> {
+        let mut routines = routines.iter().map(|(addr, stats)| (*addr, *stats)).collect::<Vec<_>>();
+        routines.sort_by(|a, b| b.1.exclusive.cmp(&a.1.exclusive).then(a.0.cmp(&b.0)));
+        routines
+    }
+
+    fn enter_root(&mut self, pc: u16, sp: u8) {
+        if self.stack.is_empty() {
+            self.call(pc, sp);
+        }
+    }
+
+    fn call(&mut self, addr: u16, sp: u8) {
+        self.stack.push(Frame { addr: addr, sp: sp });
+        *self.running.entry(addr).or_insert(0) += 1;
+        self.frame.entry(addr).or_insert_with(RoutineStats::default).calls += 1;
+    }
+
+    /// Leaves the subroutines whose return address is popped, when S is `sp`.
+    fn unwind(&mut self, sp: u8) {
+        // the root isn't returned from, even if the program plays tricks with the stack.
+        while self.stack.len() > 1 && self.stack.last().map_or(false, |frame| frame.sp as u16 + 2 <= sp as u16) {
+            let frame = self.stack.pop().unwrap();
+            let count = self.running.get_mut(&frame.addr).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                self.running.remove(&frame.addr);
+            }
+        }
+    }
+
+    fn spend(&mut self, cycle: usize) {
+        let current = self.stack.last().unwrap().addr;
+        self.frame.entry(current).or_insert_with(RoutineStats::default).exclusive += cycle;
+
+        // a recursive routine is counted once.
+        for addr in self.running.keys() {
+            self.frame.entry(*addr).or_insert_with(RoutineStats::default).inclusive += cycle;
+        }
+    }
+}
+
+#[cfg(test)]
+mod profiler_test {
+    use super::*;
+
+    const JSR: u8 = 0x20;
+    const RTS: u8 = 0x60;
+    const RTI: u8 = 0x40;
+    const NOP: u8 = 0xEA;
+    const PHA: u8 = 0x48;
+    const PLA: u8 = 0x68;
+    const JMP: u8 = 0x4C;
+
+    fn stats(calls: usize, inclusive: usize, exclusive: usize) -> RoutineStats {
+        RoutineStats { calls: calls, inclusive: inclusive, exclusive: exclusive }
+    }
+
+    #[test]
+    fn execute_test() {
+        let mut profiler = Profiler::new();
+        // 8000: JSR $9000; 9000: NOP; JSR $A000; A000: RTS; 9004: RTS
+        profiler.execute(0x8000, JSR, 0x9000, 0xFB, 6);
+        profiler.execute(0x9000, NOP, 0x9001, 0xFB, 2);
+        profiler.execute(0x9001, JSR, 0xA000, 0xF9, 6);
+        profiler.execute(0xA000, RTS, 0x9004, 0xFB, 6);
+        profiler.execute(0x9004, RTS, 0x8003, 0xFD, 6);
+        profiler.execute(0x8003, NOP, 0x8004, 0xFD, 2);
+        profiler.end_frame();
+
+        assert_eq!(profiler.last_frame(), vec![
+            (0x9000, stats(1, 20, 14)),
+            (0x8000, stats(1, 28, 8)),
+            (0xA000, stats(1, 6, 6)),
+        ]);
+
+        // the root isn't returned from.
+        profiler.execute(0x8004, RTS, 0x8000, 0xFF, 6);
+        profiler.execute(0x8000, JSR, 0x9000, 0xFD, 6);
+        profiler.end_frame();
+        assert_eq!(profiler.frames(), 2);
+        assert_eq!(profiler.last_frame(), vec![(0x8000, stats(0, 12, 12)), (0x9000, stats(1, 0, 0))]);
+        assert_eq!(profiler.total()[0], (0x8000, stats(1, 40, 20)));
+    }
+
+    #[test]
+    fn execute_stack_tricks_test() {
+        let mut profiler = Profiler::new();
+        // 8000: JSR $9000; 9000: PLA; PLA returns without RTS, so JMP $8003 belongs to the caller.
+        profiler.execute(0x8000, JSR, 0x9000, 0xFB, 6);
+        profiler.execute(0x9000, PLA, 0x9001, 0xFC, 4);
+        profiler.execute(0x9001, PLA, 0x9002, 0xFD, 4);
+        profiler.execute(0x9002, JMP, 0x8003, 0xFD, 3);
+        // 8003: JSR $9100; 9100: PHA; PHA; RTS jumps to $A000 through the table, A000: RTS returns.
+        profiler.execute(0x8003, JSR, 0x9100, 0xFB, 6);
+        profiler.execute(0x9100, PHA, 0x9101, 0xFA, 3);
+        profiler.execute(0x9101, PHA, 0x9102, 0xF9, 3);
+        profiler.execute(0x9102, RTS, 0xA000, 0xFB, 6);
+        profiler.execute(0xA000, RTS, 0x8006, 0xFD, 6);
+        profiler.execute(0x8006, NOP, 0x8007, 0xFD, 2);
+        profiler.end_frame();
+
+        assert_eq!(profiler.last_frame(), vec![
+            (0x9100, stats(1, 18, 18)),
+            (0x8000, stats(1, 43, 17)),
+            (0x9000, stats(1, 8, 8)),
+        ]);
+    }
+
+    #[test]
+    fn interrupt_test() {
+        let mut profiler = Profiler::new();
+        profiler.execute(0x8000, NOP, 0x8001, 0xFD, 2);
+        profiler.interrupt(0x8001, 0xC000, 0xFA, 7);
+        profiler.execute(0xC000, RTI, 0x8001, 0xFD, 6);
+        profiler.execute(0x8001, NOP, 0x8002, 0xFD, 2);
+        profiler.end_frame();
+
+        assert_eq!(profiler.last_frame(), vec![(0xC000, stats(1, 13, 13)), (0x8000, stats(1, 17, 4))]);
+    }
+
+    struct LabelsMock;
+
+    impl Labels for LabelsMock {
+        fn label(&self, addr: u16) -> Option<&str> {
+            if addr == 0x9000 { Some("update") } else { None }
+        }
+    }
+
+    #[test]
+    fn report_test() {
+        let mut profiler = Profiler::new();
+        profiler.execute(0x8000, JSR, 0x9000, 0xFB, 6);
+        profiler.execute(0x9000, RTS, 0x8003, 0xFD, 6);
+        profiler.end_frame();
+        profiler.execute(0x8003, NOP, 0x8004, 0xFD, 2);
+        profiler.end_frame();
+
+        assert_eq!(profiler.report(Some(&LabelsMock)), "\
+2 frames
+routine                       calls    inclusive    exclusive
+average per frame:
+$8000                           0.5          7.0          4.0
+update ($9000)                  0.5          3.0          3.0
+last frame:
+$8000                           0.0          2.0          2.0");
+    }
+}
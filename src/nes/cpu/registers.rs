@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Registers {
     pub A: u8,
     pub X: u8,
@@ -40,7 +40,7 @@ impl Registers {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Status {
     pub negative: bool,
     pub overflow: bool,
@@ -0,0 +1,15 @@
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CpuState {
+    Running,
+    /// KIL/JAM was executed at `pc`. The CPU doesn't fetch anymore until reset.
+    Jammed { pc: u16, opecode: u8 },
+}
+
+impl CpuState {
+    pub fn is_jammed(&self) -> bool {
+        match *self {
+            CpuState::Jammed { .. } => true,
+            CpuState::Running => false,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Timing {
+    /// An instruction is executed at once and its cycle is looked up from the opecode table.
+    Instruction,
+    /// Every bus access takes its own CPU cycle and the bus is ticked between them.
+    /// Dummy reads and the double write of read-modify-write instructions are performed.
+    CycleStepped,
+}
+
+impl Timing {
+    pub fn is_cycle_stepped(&self) -> bool {
+        match *self {
+            Timing::Instruction => false,
+            Timing::CycleStepped => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod timing_test {
+    use super::*;
+
+    #[test]
+    fn is_cycle_stepped_test() {
+        assert_eq!(Timing::Instruction.is_cycle_stepped(), false);
+        assert_eq!(Timing::CycleStepped.is_cycle_stepped(), true);
+    }
+}
@@ -0,0 +1,179 @@
+use std::io::Write;
+
+use super::bus::CpuBus;
+use super::disassembler::{self, disassemble_with_syntax, Labels, NoLabels, Syntax};
+use super::opecode::{self, AddressingMode, Command};
+use super::registers::Registers;
+
+/// Receives 1 line per instruction before it is executed.
+pub trait Tracer {
+    fn trace(&mut self, line: &str);
+}
+
+impl<W: Write> Tracer for W {
+    fn trace(&mut self, line: &str) {
+        // a broken trace output must not stop the emulation.
+        let _ = writeln!(self, "{}", line);
+    }
+}
+
+/// Formats the instruction at PC like the log of Nintendulator, which `nestest.log` is written in.
+/// `cycle` is the CPU cycle spent before the instruction.
+/// The addresses of operands are shown by `labels` if they are given.
+pub fn format_line<T: CpuBus>(registers: &Registers, bus: &mut T, cycle: usize, labels: Option<&dyn Labels>) -> String {
+    let pc = registers.PC;
+    let (text, length) = disassemble_with_syntax(bus, pc, labels.unwrap_or(&NoLabels), Syntax::Nintendulator);
+
+    let bytes = (0..length)
+        .map(|i| format!("{:02X}", bus.peek(pc.wrapping_add(i))))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // unofficial opecodes are marked with '*', and ISC is called ISB.
+    let (marker, text) = match opecode::decode(bus.peek(pc)) {
+        Some(rule) if !rule.official => ('*', text.replacen("ISC", "ISB", 1)),
+        _ => (' ', text),
+    };
+    let text = format!("{}{}", text, effective_address(registers, bus));
+    let (line, dot) = bus.ppu_position();
+
+    format!(
+        "{:04X}  {:<8} {}{:<31} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:>3},{:>3} CYC:{}",
+        pc, bytes, marker, text,
+        registers.A, registers.X, registers.Y,
+        // B flag doesn't exist in the register.
+        registers.P.to_bit() & 0xEF, registers.S,
+        line, dot, cycle,
+    )
+}
+
+/// The effective address and the data in it, which nestest appends to the disassembly.
+fn effective_address<T: CpuBus>(registers: &Registers, bus: &mut T) -> String {
+    let pc = registers.PC;
+    let rule = match opecode::decode(bus.peek(pc)) {
+        Some(rule) => rule,
+        None => return String::new(),
+    };
+
+    let operand = bus.peek(pc.wrapping_add(1));
+    let absolute = (bus.peek(pc.wrapping_add(2)) as u16) << 8 | operand as u16;
+
+    match rule.mode {
+        AddressingMode::ZeroPage => return format!(" = {:02X}", bus.peek(operand as u16)),
+        // the destination of jumps is already shown.
+        AddressingMode::Absolute if rule.command == Command::JMP || rule.command == Command::JSR => return String::new(),
+        AddressingMode::Absolute => return format!(" = {:02X}", bus.peek(absolute)),
+        _ => {},
+    }
+
+    let effective = match disassembler::effective_address(registers, bus) {
+        Some(effective) => effective,
+        None => return String::new(),
+    };
+    let (addr, indirect) = (effective.addr, effective.indirect.unwrap_or(0));
+    match rule.mode {
+        AddressingMode::ZeroPageX | AddressingMode::ZeroPageY => format!(" @ {:02X} = {:02X}", addr, bus.peek(addr)),
+        AddressingMode::AbsoluteX | AddressingMode::AbsoluteY => format!(" @ {:04X} = {:02X}", addr, bus.peek(addr)),
+        AddressingMode::IndirectAbsolute => format!(" = {:04X}", addr),
+        AddressingMode::PreIndexedIndirect => format!(" @ {:02X} = {:04X} = {:02X}", indirect, addr, bus.peek(addr)),
+        AddressingMode::PostIndexedIndirect => format!(" = {:04X} @ {:04X} = {:02X}", indirect, addr, bus.peek(addr)),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tracer_test {
+    use super::*;
+
+    struct BusMock {
+        ram: Vec<u8>,
+    }
+
+    impl BusMock {
+        fn new(program: &[u8]) -> Self {
+            let mut ram = vec![0; 0x10000];
+            ram[0xC000..0xC000 + program.len()].copy_from_slice(program);
+            Self { ram: ram }
+        }
+    }
+
+    impl CpuBus for BusMock {
+        fn read(&mut self, addr: u16) -> u8 {
+            self.ram[addr as usize]
+        }
+
+        fn read_twice(&mut self, addr: u16) -> u16 {
+            let lower = self.ram[addr as usize] as u16;
+            let upper = self.ram[addr as usize + 1] as u16;
+            lower | upper << 8
+        }
+
+        fn write(&mut self, addr: u16, data: u8) {
+            self.ram[addr as usize] = data;
+        }
+
+        fn ppu_position(&self) -> (usize, usize) {
+            (0, 21)
+        }
+    }
+
+    fn registers() -> Registers {
+        let mut registers = Registers::new();
+        registers.PC = 0xC000;
+        registers
+    }
+
+    #[test]
+    fn format_line_test() {
+        // the first line of nestest.log
+        let mut bus = BusMock::new(&[0x4C, 0xF5, 0xC5]);
+        assert_eq!(
+            format_line(&registers(), &mut bus, 7, None),
+            "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7"
+        );
+    }
+
+    #[test]
+    fn format_line_effective_address_test() {
+        let mut bus = BusMock::new(&[0xB1, 0x89]);
+        bus.ram[0x0089] = 0x00;
+        bus.ram[0x008A] = 0x03;
+        bus.ram[0x0334] = 0x89;
+        let mut registers = registers();
+        registers.Y = 0x34;
+        assert_eq!(
+            format_line(&registers, &mut bus, 100, None),
+            "C000  B1 89     LDA ($89),Y = 0300 @ 0334 = 89  A:00 X:00 Y:34 P:24 SP:FD PPU:  0, 21 CYC:100"
+        );
+
+        let mut bus = BusMock::new(&[0x6C, 0xFF, 0x02]);
+        bus.ram[0x02FF] = 0x00;
+        bus.ram[0x0200] = 0x03;
+        assert!(format_line(&registers, &mut bus, 0, None).contains("JMP ($02FF) = 0300"));
+
+        let mut bus = BusMock::new(&[0xA1, 0x80]);
+        bus.ram[0x00B4] = 0x00;
+        bus.ram[0x00B5] = 0x02;
+        bus.ram[0x0200] = 0x5A;
+        registers.X = 0x34;
+        assert!(format_line(&registers, &mut bus, 0, None).contains("LDA ($80,X) @ B4 = 0200 = 5A"));
+    }
+
+    #[test]
+    fn format_line_absolute_zero_page_test() {
+        // nestest.log doesn't mark the absolute addressing of the zero page.
+        let mut bus = BusMock::new(&[0xAD, 0x10, 0x00]);
+        bus.ram[0x0010] = 0x5A;
+        assert_eq!(
+            format_line(&registers(), &mut bus, 7, None),
+            "C000  AD 10 00  LDA $0010 = 5A                  A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7"
+        );
+    }
+
+    #[cfg(feature = "illegal-opcodes")]
+    #[test]
+    fn format_line_unofficial_test() {
+        let mut bus = BusMock::new(&[0xE7, 0x10]);
+        assert!(format_line(&registers(), &mut bus, 0, None).starts_with("C000  E7 10    *ISB $10 = 00"));
+    }
+}
@@ -0,0 +1,27 @@
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Variant {
+    /// Ricoh 2A03 of the NES. The decimal flag can be set, but ADC/SBC ignore it.
+    Ricoh2A03,
+    /// Generic NMOS 6502. ADC/SBC perform BCD arithmetic when the decimal flag is set.
+    Mos6502,
+}
+
+impl Variant {
+    pub fn has_decimal_mode(&self) -> bool {
+        match *self {
+            Variant::Ricoh2A03 => false,
+            Variant::Mos6502 => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod variant_test {
+    use super::*;
+
+    #[test]
+    fn has_decimal_mode_test() {
+        assert_eq!(Variant::Ricoh2A03.has_decimal_mode(), false);
+        assert_eq!(Variant::Mos6502.has_decimal_mode(), true);
+    }
+}
@@ -0,0 +1,445 @@
+use std::fmt;
+
+use nes::cpu::Registers;
+
+/// What an expression is evaluated against.
+pub trait Context {
+    fn registers(&self) -> &Registers;
+    /// Reads the memory without side effects.
+    fn read(&mut self, addr: u16) -> u8;
+    /// Returns the scanline and the cycle in it of the PPU.
+    fn ppu_position(&self) -> (usize, usize);
+}
+
+/// A condition like `A == $40 && [$0300] > 3` or `P.carry && scanline == 240`.
+///
+/// Numbers are decimal, or hexadecimal with '$' or "0x". `[addr]` reads 1 byte of the memory.
+/// Variables are A, X, Y, S (SP), P, PC, the flags of P like `P.carry`,
+/// and the PPU position `scanline` (`line`) and `cycle` (`dot`).
+/// Operators are `! -` (unary), `+ -`, `&`, `^`, `|`, comparisons, `&&` and `||` in the order of precedence.
+/// The result is true when it isn't 0.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Node {
+    Number(i64),
+    Variable(Variable),
+    Memory(Box<Node>),
+    Unary(UnaryOperator, Box<Node>),
+    Binary(BinaryOperator, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Variable {
+    A,
+    X,
+    Y,
+    S,
+    P,
+    PC,
+    Negative,
+    Overflow,
+    Decimal,
+    Interrupt,
+    Zero,
+    Carry,
+    Scanline,
+    Cycle,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum UnaryOperator {
+    Not,
+    Negate,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum BinaryOperator {
+    Add,
+    Subtract,
+    BitAnd,
+    BitXor,
+    BitOr,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Number(i64),
+    Identifier(String),
+    Symbol(&'static str),
+}
+
+// longer symbols first, so that "&&" isn't read as "&".
+const SYMBOLS: [&str; 19] = [
+    "&&", "||", "==", "!=", "<=", ">=",
+    "<", ">", "!", "-", "+", "&", "^", "|", "(", ")", "[", "]", ".",
+];
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Expression, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens: tokens, position: 0 };
+        let root = parser.parse_or()?;
+
+        match parser.peek() {
+            None => Ok(Expression { source: String::from(source.trim()), root: root }),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    pub fn evaluate<C: Context>(&self, context: &mut C) -> i64 {
+        evaluate(&self.root, context)
+    }
+
+    pub fn is_true<C: Context>(&self, context: &mut C) -> bool {
+        self.evaluate(context) != 0
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut rest = source.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let length = if c == '$' || rest.starts_with("0x") {
+            let prefix = if c == '$' { 1 } else { 2 };
+            let length = prefix + rest[prefix..].find(|c: char| !c.is_digit(16)).unwrap_or(rest.len() - prefix);
+            let number = i64::from_str_radix(&rest[prefix..length], 16)
+                .map_err(|_| format!("invalid number: {}", &rest[..length]))?;
+            tokens.push(Token::Number(number));
+            length
+        } else if c.is_digit(10) {
+            let length = rest.find(|c: char| !c.is_digit(10)).unwrap_or(rest.len());
+            let number = rest[..length].parse().map_err(|_| format!("invalid number: {}", &rest[..length]))?;
+            tokens.push(Token::Number(number));
+            length
+        } else if c.is_alphabetic() || c == '_' {
+            let length = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+            tokens.push(Token::Identifier(String::from(&rest[..length])));
+            length
+        } else {
+            match SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)) {
+                Some(symbol) => {
+                    tokens.push(Token::Symbol(symbol));
+                    symbol.len()
+                },
+                None => return Err(format!("unexpected '{}'", c)),
+            }
+        };
+
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// Consumes the symbol if it is the next token.
+    fn accept(&mut self, symbol: &str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol_of(symbol))) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.accept(symbol) {
+            Ok(())
+        } else {
+            Err(format!("'{}' is expected", symbol))
+        }
+    }
+
+    /// Parses the binary operators of the same precedence, from left to right.
+    fn parse_binary<F>(&mut self, operators: &[(&str, BinaryOperator)], mut parse_operand: F) -> Result<Node, String>
+        where F: FnMut(&mut Parser) -> Result<Node, String> {
+        let mut node = parse_operand(self)?;
+        'outer: loop {
+            for &(symbol, operator) in operators {
+                if self.accept(symbol) {
+                    let right = parse_operand(self)?;
+                    node = Node::Binary(operator, Box::new(node), Box::new(right));
+                    continue 'outer;
+                }
+            }
+            return Ok(node);
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Node, String> {
+        self.parse_binary(&[("||", BinaryOperator::Or)], Parser::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, String> {
+        self.parse_binary(&[("&&", BinaryOperator::And)], Parser::parse_comparison)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Node, String> {
+        self.parse_binary(&[
+            ("==", BinaryOperator::Equal),
+            ("!=", BinaryOperator::NotEqual),
+            ("<=", BinaryOperator::LessEqual),
+            (">=", BinaryOperator::GreaterEqual),
+            ("<", BinaryOperator::Less),
+            (">", BinaryOperator::Greater),
+        ], Parser::parse_bit_or)
+    }
+
+    fn parse_bit_or(&mut self) -> Result<Node, String> {
+        self.parse_binary(&[("|", BinaryOperator::BitOr)], Parser::parse_bit_xor)
+    }
+
+    fn parse_bit_xor(&mut self) -> Result<Node, String> {
+        self.parse_binary(&[("^", BinaryOperator::BitXor)], Parser::parse_bit_and)
+    }
+
+    fn parse_bit_and(&mut self) -> Result<Node, String> {
+        self.parse_binary(&[("&", BinaryOperator::BitAnd)], Parser::parse_sum)
+    }
+
+    fn parse_sum(&mut self) -> Result<Node, String> {
+        self.parse_binary(&[("+", BinaryOperator::Add), ("-", BinaryOperator::Subtract)], Parser::parse_unary)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, String> {
+        if self.accept("!") {
+            return Ok(Node::Unary(UnaryOperator::Not, Box::new(self.parse_unary()?)));
+        }
+        if self.accept("-") {
+            return Ok(Node::Unary(UnaryOperator::Negate, Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Node::Number(number)),
+            Some(Token::Identifier(name)) => self.parse_variable(&name),
+            Some(Token::Symbol("(")) => {
+                let node = self.parse_or()?;
+                self.expect(")")?;
+                Ok(node)
+            },
+            Some(Token::Symbol("[")) => {
+                let node = self.parse_or()?;
+                self.expect("]")?;
+                Ok(Node::Memory(Box::new(node)))
+            },
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err(String::from("unexpected end")),
+        }
+    }
+
+    fn parse_variable(&mut self, name: &str) -> Result<Node, String> {
+        let variable = match name {
+            "A" => Variable::A,
+            "X" => Variable::X,
+            "Y" => Variable::Y,
+            "S" | "SP" => Variable::S,
+            "PC" => Variable::PC,
+            "scanline" | "line" => Variable::Scanline,
+            "cycle" | "dot" => Variable::Cycle,
+            "P" if self.accept(".") => {
+                let flag = match self.next() {
+                    Some(Token::Identifier(flag)) => flag,
+                    _ => return Err(String::from("a flag of P is expected")),
+                };
+                match flag.as_str() {
+                    "negative" => Variable::Negative,
+                    "overflow" => Variable::Overflow,
+                    "decimal" => Variable::Decimal,
+                    "interrupt" => Variable::Interrupt,
+                    "zero" => Variable::Zero,
+                    "carry" => Variable::Carry,
+                    _ => return Err(format!("unknown flag: P.{}", flag)),
+                }
+            },
+            "P" => Variable::P,
+            _ => return Err(format!("unknown variable: {}", name)),
+        };
+        Ok(Node::Variable(variable))
+    }
+}
+
+/// Returns the static symbol which equals to `symbol`.
+fn symbol_of(symbol: &str) -> &'static str {
+    SYMBOLS.iter().find(|s| **s == symbol).expect("unknown symbol")
+}
+
+fn evaluate<C: Context>(node: &Node, context: &mut C) -> i64 {
+    match *node {
+        Node::Number(number) => number,
+        Node::Variable(variable) => read_variable(variable, context),
+        Node::Memory(ref addr) => {
+            let addr = evaluate(addr, context);
+            context.read(addr as u16) as i64
+        },
+        Node::Unary(operator, ref operand) => {
+            let operand = evaluate(operand, context);
+            match operator {
+                UnaryOperator::Not => (operand == 0) as i64,
+                UnaryOperator::Negate => operand.wrapping_neg(),
+            }
+        },
+        Node::Binary(operator, ref left, ref right) => {
+            let left = evaluate(left, context);
+            // && and || don't evaluate the right side when the left decides the result.
+            match operator {
+                BinaryOperator::And if left == 0 => return 0,
+                BinaryOperator::Or if left != 0 => return 1,
+                _ => {},
+            }
+            let right = evaluate(right, context);
+
+            match operator {
+                BinaryOperator::Add => left.wrapping_add(right),
+                BinaryOperator::Subtract => left.wrapping_sub(right),
+                BinaryOperator::BitAnd => left & right,
+                BinaryOperator::BitXor => left ^ right,
+                BinaryOperator::BitOr => left | right,
+                BinaryOperator::Equal => (left == right) as i64,
+                BinaryOperator::NotEqual => (left != right) as i64,
+                BinaryOperator::Less => (left < right) as i64,
+                BinaryOperator::LessEqual => (left <= right) as i64,
+                BinaryOperator::Greater => (left > right) as i64,
+                BinaryOperator::GreaterEqual => (left >= right) as i64,
+                BinaryOperator::And | BinaryOperator::Or => (right != 0) as i64,
+            }
+        },
+    }
+}
+
+fn read_variable<C: Context>(variable: Variable, context: &mut C) -> i64 {
+    let (scanline, cycle) = context.ppu_position();
+    let registers = context.registers();
+
+    match variable {
+        Variable::A => registers.A as i64,
+        Variable::X => registers.X as i64,
+        Variable::Y => registers.Y as i64,
+        Variable::S => registers.S as i64,
+        Variable::P => registers.P.to_bit() as i64,
+        Variable::PC => registers.PC as i64,
+        Variable::Negative => registers.P.negative as i64,
+        Variable::Overflow => registers.P.overflow as i64,
+        Variable::Decimal => registers.P.decimal as i64,
+        Variable::Interrupt => registers.P.interrupt as i64,
+        Variable::Zero => registers.P.zero as i64,
+        Variable::Carry => registers.P.carry as i64,
+        Variable::Scanline => scanline as i64,
+        Variable::Cycle => cycle as i64,
+    }
+}
+
+#[cfg(test)]
+mod expression_test {
+    use super::*;
+
+    struct ContextMock {
+        registers: Registers,
+        ram: Vec<u8>,
+        reads: usize,
+    }
+
+    impl ContextMock {
+        fn new() -> Self {
+            Self { registers: Registers::new(), ram: vec![0; 0x10000], reads: 0 }
+        }
+    }
+
+    impl Context for ContextMock {
+        fn registers(&self) -> &Registers {
+            &self.registers
+        }
+
+        fn read(&mut self, addr: u16) -> u8 {
+            self.reads += 1;
+            self.ram[addr as usize]
+        }
+
+        fn ppu_position(&self) -> (usize, usize) {
+            (240, 10)
+        }
+    }
+
+    fn evaluate(source: &str, context: &mut ContextMock) -> i64 {
+        Expression::parse(source).unwrap().evaluate(context)
+    }
+
+    #[test]
+    fn evaluate_test() {
+        let mut context = ContextMock::new();
+        context.registers.A = 0x40;
+        context.registers.P.carry = true;
+        context.ram[0x0300] = 4;
+
+        assert_eq!(evaluate("A == $40 && [$0300] > 3", &mut context), 1);
+        assert_eq!(evaluate("P.carry && scanline == 240", &mut context), 1);
+        assert_eq!(evaluate("!P.zero && dot != 10", &mut context), 0);
+        assert_eq!(evaluate("[0x2FF + 1] - 5", &mut context), -1);
+        assert_eq!(evaluate("A & $0F | 3 ^ 1", &mut context), 2);
+        assert_eq!(evaluate("(1 + 2) == 3 || PC < SP", &mut context), 1);
+        assert_eq!(evaluate("P", &mut context), 0x35);
+    }
+
+    #[test]
+    fn short_circuit_test() {
+        let mut context = ContextMock::new();
+        assert_eq!(evaluate("X != 0 && [$00] == 0", &mut context), 0);
+        assert_eq!(evaluate("X == 0 || [$00] == 0", &mut context), 1);
+        assert_eq!(context.reads, 0);
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert!(Expression::parse("").is_err());
+        assert!(Expression::parse("A ==").is_err());
+        assert!(Expression::parse("(A == 1").is_err());
+        assert!(Expression::parse("[A").is_err());
+        assert!(Expression::parse("A B").is_err());
+        assert!(Expression::parse("Q == 1").is_err());
+        assert!(Expression::parse("P.foo").is_err());
+        assert!(Expression::parse("$").is_err());
+        assert!(Expression::parse("A @ 1").is_err());
+    }
+
+    #[test]
+    fn display_test() {
+        let expression = Expression::parse(" A == $40 ").unwrap();
+        assert_eq!(expression.to_string(), "A == $40");
+    }
+}
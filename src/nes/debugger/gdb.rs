@@ -0,0 +1,444 @@
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+
+use nes::cpu::CpuError;
+use super::{Debugger, Stop, WatchKind};
+
+// signals of the stop reply
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+/// Ctrl-C from the client while the target is running.
+const INTERRUPT: u8 = 0x03;
+/// Instructions executed between the checks of the interrupt.
+const INTERRUPT_CHECK_INTERVAL: usize = 10000;
+/// The max length of a packet, which is advertised by qSupported.
+const PACKET_SIZE: usize = 0x1000;
+
+/// A stub of the GDB remote serial protocol, which accepts only the loopback connection.
+///
+/// The registers of `g` and `G` packets are A, X, Y, P, S (1 byte each) and PC (2 bytes, little endian).
+pub struct GdbServer {
+    listener: TcpListener,
+}
+
+impl GdbServer {
+    /// Listens on 127.0.0.1:`port`. The port 0 picks a free port.
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        Ok(Self { listener: listener })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts 1 client, and serves it until it detaches, kills or disconnects.
+    pub fn serve(&self, debugger: &mut Debugger) -> io::Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        let mut session = Session { stream: stream, debugger: debugger };
+        session.run()
+    }
+}
+
+struct Session<'a> {
+    stream: TcpStream,
+    debugger: &'a mut Debugger,
+}
+
+impl <'a> Session<'a> {
+    fn run(&mut self) -> io::Result<()> {
+        while let Some(packet) = self.read_packet()? {
+            match packet.as_str() {
+                // the target keeps the state after the client leaves.
+                "D" => return self.write_packet("OK"),
+                "k" => return Ok(()),
+                _ => {
+                    let response = self.handle(&packet);
+                    self.write_packet(&response)?;
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle(&mut self, packet: &str) -> String {
+        let command = match packet.chars().next() {
+            Some(command) => command,
+            None => return String::new(),
+        };
+
+        let args = &packet[command.len_utf8()..];
+        match command {
+            '?' => stop_reply(Stop::Step),
+            'g' => self.read_registers(),
+            'G' => self.write_registers(args),
+            'm' => self.read_memory(args),
+            'M' => self.write_memory(args),
+            's' => {
+                self.jump(args);
+                let stop = self.debugger.step();
+                stop_reply(stop)
+            },
+            'c' => {
+                self.jump(args);
+                match self.resume() {
+                    Some(stop) => stop_reply(stop),
+                    None => Some(format!("S{:02x}", SIGINT)),
+                }
+            },
+            // backward stepping of the reverse execution
+            'b' if args == "s" => {
+                let stop = self.debugger.step_back();
+                stop_reply(stop)
+            },
+            'Z' => self.set_breakpoint(args, true),
+            'z' => self.set_breakpoint(args, false),
+            'H' => Some(String::from("OK")),
+            'q' if packet.starts_with("qSupported") => Some(format!("PacketSize={:x};ReverseStep+", PACKET_SIZE)),
+            'q' if packet == "qAttached" => Some(String::from("1")),
+            // unsupported packets are answered with the empty response.
+            _ => Some(String::new()),
+        }
+        .unwrap_or_else(|| String::from("E01"))
+    }
+
+    fn read_registers(&self) -> Option<String> {
+        let registers = self.debugger.registers();
+        let bytes = [
+            registers.A, registers.X, registers.Y, registers.P.to_bit(), registers.S,
+            registers.PC as u8, (registers.PC >> 8) as u8,
+        ];
+        Some(encode_hex(&bytes))
+    }
+
+    fn write_registers(&mut self, args: &str) -> Option<String> {
+        let bytes = decode_hex(args)?;
+        if bytes.len() != 7 {
+            return None;
+        }
+
+        let registers = self.debugger.registers_mut();
+        registers.A = bytes[0];
+        registers.X = bytes[1];
+        registers.Y = bytes[2];
+        registers.P.set_by_bit(bytes[3]);
+        registers.S = bytes[4];
+        registers.PC = (bytes[6] as u16) << 8 | bytes[5] as u16;
+        Some(String::from("OK"))
+    }
+
+    fn read_memory(&mut self, args: &str) -> Option<String> {
+        let (addr, len) = parse_pair(args, ',')?;
+        // the hex of the memory has to fit in a packet.
+        if len > PACKET_SIZE / 2 {
+            return None;
+        }
+
+        let memory = self.debugger.read_memory(addr as u16, len);
+        Some(encode_hex(&memory))
+    }
+
+    fn write_memory(&mut self, args: &str) -> Option<String> {
+        let mut parts = args.splitn(2, ':');
+        let (addr, len) = parse_pair(parts.next()?, ',')?;
+        if len > PACKET_SIZE / 2 {
+            return None;
+        }
+
+        let data = decode_hex(parts.next()?)?;
+        if data.len() != len {
+            return None;
+        }
+
+        for (i, byte) in data.iter().enumerate() {
+            self.debugger.write_memory((addr as u16).wrapping_add(i as u16), *byte);
+        }
+        Some(String::from("OK"))
+    }
+
+    /// `type,addr,kind` of Z and z packets.
+    fn set_breakpoint(&mut self, args: &str, insert: bool) -> Option<String> {
+        let mut parts = args.split(',');
+        let kind = parts.next()?;
+        let addr = usize::from_str_radix(parts.next()?, 16).ok()? as u16;
+
+        let watch_kind = match kind {
+            // software and hardware breakpoints are the same for the emulator.
+            "0" | "1" => {
+                if insert {
+                    self.debugger.add_breakpoint(addr);
+                } else {
+                    self.debugger.remove_breakpoint(addr);
+                }
+                return Some(String::from("OK"));
+            },
+            "2" => WatchKind::Write,
+            "3" => WatchKind::Read,
+            "4" => WatchKind::Access,
+            _ => return Some(String::new()),
+        };
+
+        if insert {
+            self.debugger.add_watchpoint(addr, watch_kind);
+        } else {
+            self.debugger.remove_watchpoint(addr);
+        }
+        Some(String::from("OK"))
+    }
+
+    /// `c` and `s` packets can have the address to resume at.
+    fn jump(&mut self, args: &str) {
+        if let Ok(addr) = u16::from_str_radix(args, 16) {
+            self.debugger.registers_mut().PC = addr;
+        }
+    }
+
+    /// Returns None when the client interrupted.
+    fn resume(&mut self) -> Option<Stop> {
+        loop {
+            match self.debugger.resume_for(INTERRUPT_CHECK_INTERVAL) {
+                Stop::Step => {},
+                stop => return Some(stop),
+            }
+
+            if self.is_interrupted() {
+                return None;
+            }
+        }
+    }
+
+    fn is_interrupted(&mut self) -> bool {
+        let mut byte = [0];
+        if self.stream.set_nonblocking(true).is_err() {
+            return false;
+        }
+        let result = self.stream.read(&mut byte);
+        let _ = self.stream.set_nonblocking(false);
+
+        match result {
+            Ok(1) => byte[0] == INTERRUPT,
+            _ => false,
+        }
+    }
+
+    /// Reads a packet and acknowledges it. Returns None when the client has disconnected.
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            match self.read_byte()? {
+                Some(b'$') => {},
+                // acknowledgements and interrupts out of running are ignored.
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+
+            // the data over the advertised size isn't kept, and the packet is rejected.
+            let mut data = vec![];
+            let mut overflow = false;
+            loop {
+                match self.read_byte()? {
+                    Some(b'#') => break,
+                    Some(_) if data.len() >= PACKET_SIZE => overflow = true,
+                    Some(byte) => data.push(byte),
+                    None => return Ok(None),
+                }
+            }
+
+            let mut checksum = [0; 2];
+            self.stream.read_exact(&mut checksum)?;
+            let valid = String::from_utf8(checksum.to_vec()).ok()
+                .and_then(|checksum| u8::from_str_radix(&checksum, 16).ok())
+                .map_or(false, |checksum| checksum == sum(&data));
+
+            if overflow || !valid {
+                self.stream.write_all(b"-")?;
+                continue;
+            }
+
+            self.stream.write_all(b"+")?;
+            return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+        }
+    }
+
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        let packet = format!("${}#{:02x}", data, sum(data.as_bytes()));
+        self.stream.write_all(packet.as_bytes())
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        match self.stream.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+}
+
+fn stop_reply(stop: Stop) -> Option<String> {
+    let reply = match stop {
+        Stop::Watchpoint(hit) => {
+            let kind = match hit.kind {
+                WatchKind::Write => "watch",
+                WatchKind::Read => "rwatch",
+                WatchKind::Access => "awatch",
+            };
+            format!("T{:02x}{}:{:04x};", SIGTRAP, kind, hit.addr)
+        },
+        Stop::HistoryStart => format!("T{:02x}replaylog:begin;", SIGTRAP),
+        Stop::Jammed | Stop::Error(CpuError::UnknownOpecode { .. }) => format!("S{:02x}", SIGILL),
+        _ => format!("S{:02x}", SIGTRAP),
+    };
+    Some(reply)
+}
+
+fn parse_pair(args: &str, separator: char) -> Option<(usize, usize)> {
+    let mut parts = args.splitn(2, separator);
+    let first = usize::from_str_radix(parts.next()?, 16).ok()?;
+    let second = usize::from_str_radix(parts.next()?, 16).ok()?;
+    Some((first, second))
+}
+
+fn sum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len()).step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod gdb_test {
+    use super::*;
+    use std::thread;
+    use super::super::debugger_test::debugger;
+
+    /// Sends `packets` from a loopback client, and returns the responses without the framing.
+    fn request(debugger: &mut Debugger, packets: &'static [&'static str]) -> Vec<String> {
+        let server = GdbServer::bind(0).unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let mut responses = vec![];
+            for packet in packets {
+                let packet = format!("${}#{:02x}", packet, sum(packet.as_bytes()));
+                stream.write_all(packet.as_bytes()).unwrap();
+
+                // "+" and "$...#xx"
+                let mut response = vec![];
+                let mut byte = [0];
+                while response.len() < 3 || response[response.len() - 3] != b'#' {
+                    stream.read_exact(&mut byte).unwrap();
+                    response.push(byte[0]);
+                }
+                let response = String::from_utf8(response).unwrap();
+                responses.push(String::from(&response[2..response.len() - 3]));
+            }
+            responses
+        });
+
+        server.serve(debugger).unwrap();
+        client.join().unwrap()
+    }
+
+    #[test]
+    fn registers_and_memory_test() {
+        // LDA #$40; STA $0300
+        let mut debugger = debugger(&[0xA9, 0x40, 0x8D, 0x00, 0x03]);
+        let responses = request(&mut debugger, &[
+            "qSupported:multiprocess+",
+            "?",
+            "g",
+            "m8000,3",
+            "M0200,2:1234",
+            "G01020334fd0280",
+            "D",
+        ]);
+
+        assert_eq!(responses, vec![
+            "PacketSize=1000;ReverseStep+", "S05", "00000034fd0080", "a9408d", "OK", "OK", "OK",
+        ]);
+        assert_eq!(debugger.read_memory(0x0200, 2), vec![0x12, 0x34]);
+        assert_eq!(debugger.registers().A, 0x01);
+        assert_eq!(debugger.registers().PC, 0x8002);
+    }
+
+    #[test]
+    fn breakpoint_and_watchpoint_test() {
+        // LDA #$40; STA $0300; NOP
+        let mut debugger = debugger(&[0xA9, 0x40, 0x8D, 0x00, 0x03, 0xEA]);
+        let responses = request(&mut debugger, &[
+            "s",
+            "Z2,0300,1",
+            "c",
+            "z2,0300,1",
+            "Z0,8010,1",
+            "c",
+            "bs",
+            "D",
+        ]);
+
+        assert_eq!(responses, vec!["S05", "OK", "T05watch:0300;", "OK", "OK", "S05", "S05", "OK"]);
+        assert_eq!(debugger.registers().PC, 0x800F);
+    }
+
+    #[test]
+    fn malformed_packets_test() {
+        // LDA $0300
+        let mut debugger = debugger(&[0xAD, 0x00, 0x03]);
+        let responses = request(&mut debugger, &[
+            // a stray byte which is not ASCII
+            "\u{FFFD}",
+            "m0,ffffffffff",
+            "Mffff,2:1234",
+            "Z4,0300,1",
+            "c",
+            "D",
+        ]);
+
+        assert_eq!(responses, vec!["", "E01", "OK", "OK", "T05awatch:0300;", "OK"]);
+        assert_eq!(debugger.read_memory(0x0000, 1), vec![0x34]);
+    }
+
+    #[test]
+    fn oversized_packet_test() {
+        let mut debugger = debugger(&[]);
+        let server = GdbServer::bind(0).unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let data = vec![b'0'; PACKET_SIZE + 1];
+            stream.write_all(&[b"$", &data[..], format!("#{:02x}", sum(&data)).as_bytes()].concat()).unwrap();
+            let mut nack = [0];
+            stream.read_exact(&mut nack).unwrap();
+
+            stream.write_all(b"$D#44").unwrap();
+            let mut response = [0; 7];
+            stream.read_exact(&mut response).unwrap();
+            (nack, response)
+        });
+
+        server.serve(&mut debugger).unwrap();
+        assert_eq!(client.join().unwrap(), ([b'-'], *b"+$OK#9a"));
+    }
+
+    #[test]
+    fn decode_hex_test() {
+        assert_eq!(decode_hex("00ff"), Some(vec![0x00, 0xFF]));
+        assert_eq!(decode_hex("0"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::VecDeque;
+
+use nes::{Nes, Snapshot};
+
+/// Instructions between snapshots, which are replayed to reach the states between them.
+pub const SNAPSHOT_INTERVAL: usize = 1000;
+/// Snapshots kept at most, the oldest one is dropped first.
+pub const SNAPSHOT_CAPACITY: usize = 1024;
+
+/// Snapshots of `Nes` taken periodically, which are indexed by the number of executed instructions.
+/// The execution is deterministic, so any state after the oldest snapshot is restored by replaying.
+pub struct History {
+    interval: usize,
+    capacity: usize,
+    snapshots: VecDeque<(usize, Snapshot)>,
+}
+
+impl History {
+    pub fn new(interval: usize, capacity: usize) -> Self {
+        Self {
+            interval: interval,
+            capacity: capacity,
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    /// Takes the snapshot before the instruction of `position`, if the interval has passed since the last one.
+    pub fn record(&mut self, position: usize, nes: &Nes) {
+        let is_due = match self.snapshots.back() {
+            Some(&(last, _)) => position >= last + self.interval,
+            None => true,
+        };
+        if !is_due {
+            return;
+        }
+
+        if self.snapshots.len() >= self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back((position, nes.snapshot()));
+    }
+
+    /// Returns the latest snapshot taken at `position` or before.
+    pub fn latest_at(&self, position: usize) -> Option<(usize, &Snapshot)> {
+        self.snapshots.iter()
+            .rev()
+            .find(|&&(at, _)| at <= position)
+            .map(|&(at, ref snapshot)| (at, snapshot))
+    }
+
+    /// The position of the oldest state which can be restored.
+    pub fn oldest(&self) -> Option<usize> {
+        self.snapshots.front().map(|&(at, _)| at)
+    }
+
+    /// Drops all snapshots, when the state has been changed outside of the execution.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+#[cfg(test)]
+mod history_test {
+    use super::*;
+    use super::super::debugger_test::debugger;
+
+    #[test]
+    fn record_test() {
+        let debugger = debugger(&[]);
+        let mut history = History::new(10, 3);
+        for position in 0..35 {
+            history.record(position, debugger.nes());
+        }
+
+        // 0 has been dropped for 30.
+        assert_eq!(history.oldest(), Some(10));
+        assert_eq!(history.latest_at(29).map(|(at, _)| at), Some(20));
+        assert_eq!(history.latest_at(30).map(|(at, _)| at), Some(30));
+        assert!(history.latest_at(9).is_none());
+
+        history.clear();
+        assert_eq!(history.oldest(), None);
+        history.record(35, debugger.nes());
+        assert_eq!(history.oldest(), Some(35));
+    }
+}
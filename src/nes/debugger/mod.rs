@@ -0,0 +1,662 @@
+pub mod expression;
+pub mod gdb;
+pub mod history;
+pub mod repl;
+pub mod symbols;
+pub mod watcher;
+
+pub use self::expression::Expression;
+pub use self::symbols::{SymbolError, Symbols};
+pub use self::watcher::{WatchHit, WatchKind, Watcher, Watchpoint};
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use nes::Nes;
+use nes::cpu::{self, CpuError, Registers};
+use self::expression::Context;
+use self::history::{History, SNAPSHOT_CAPACITY, SNAPSHOT_INTERVAL};
+
+const JSR: u8 = 0x20;
+const RTS: u8 = 0x60;
+const RTI: u8 = 0x40;
+
+/// Why the execution stopped.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Stop {
+    /// The requested instructions have been executed.
+    Step,
+    /// PC reached the breakpoint.
+    Breakpoint(u16),
+    /// The last instruction accessed the watched address.
+    Watchpoint(WatchHit),
+    /// All background lines have been built.
+    Frame,
+    /// The CPU is jammed, it never progresses until reset.
+    Jammed,
+    /// The reverse execution reached the oldest recorded state.
+    HistoryStart,
+    Error(CpuError),
+}
+
+/// What a breakpoint does when PC reaches it and the condition holds.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Action {
+    Break,
+    /// Logs the instruction and the registers, and continues.
+    Log,
+}
+
+#[derive(Debug, Clone)]
+pub struct Breakpoint {
+    pub action: Action,
+    /// The breakpoint is always taken without the condition.
+    pub condition: Option<Expression>,
+}
+
+pub struct Debugger {
+    nes: Nes,
+    breakpoints: BTreeMap<u16, Breakpoint>,
+    watcher: Rc<RefCell<Watcher>>,
+    log: Vec<String>,
+    symbols: Rc<Symbols>,
+    /// The number of instructions executed by the debugger, which indexes the history.
+    position: usize,
+    history: History,
+}
+
+impl Debugger {
+    pub fn new(mut nes: Nes) -> Self {
+        let symbols = Rc::new(Symbols::new(nes.cassette().header.prg_size as usize));
+        nes.cpu_mut().set_labels(Some(symbols.clone()));
+        let watcher = Rc::new(RefCell::new(Watcher::new(vec![])));
+        nes.add_observer(watcher.clone());
+
+        Self {
+            nes: nes,
+            breakpoints: BTreeMap::new(),
+            watcher: watcher,
+            log: vec![],
+            symbols: symbols,
+            position: 0,
+            history: History::new(SNAPSHOT_INTERVAL, SNAPSHOT_CAPACITY),
+        }
+    }
+
+    pub fn nes(&self) -> &Nes {
+        &self.nes
+    }
+
+    /// Changes through it aren't recorded in the history, `clear_history` has to be called after them.
+    pub fn nes_mut(&mut self) -> &mut Nes {
+        &mut self.nes
+    }
+
+    /// The number of instructions executed by the debugger.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Forgets the recorded states, which can't be replayed after the state has been changed by hand.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Loads ca65 `.dbg` or FCEUX `.nl`, which is added to the loaded symbols.
+    pub fn load_symbols(&mut self, path: &str) -> Result<(), SymbolError> {
+        // the CPU shares the symbols, they are released to be modified in place.
+        self.nes.cpu_mut().set_labels(None);
+        let result = Rc::make_mut(&mut self.symbols).load_file(path);
+        self.nes.cpu_mut().set_labels(Some(self.symbols.clone()));
+        result
+    }
+
+    pub fn symbols(&self) -> &Symbols {
+        &self.symbols
+    }
+
+    /// Returns the address of the label, or parses hexadecimal which may be prefixed with '$' or "0x".
+    pub fn resolve_addr(&self, text: &str) -> Option<u16> {
+        if let Some(location) = self.symbols.location_of(text) {
+            return Some(location.addr);
+        }
+
+        let hex = text.trim_start_matches('$').trim_start_matches("0x");
+        u16::from_str_radix(hex, 16).ok()
+    }
+
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.set_breakpoint(addr, Breakpoint { action: Action::Break, condition: None });
+    }
+
+    /// Sets the breakpoint of `addr`, which replaces the existing one.
+    pub fn set_breakpoint(&mut self, addr: u16, breakpoint: Breakpoint) {
+        self.breakpoints.insert(addr, breakpoint);
+    }
+
+    /// Returns whether the breakpoint existed.
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr).is_some()
+    }
+
+    pub fn breakpoints(&self) -> Vec<u16> {
+        self.breakpoints.keys().cloned().collect()
+    }
+
+    /// Returns the lines logged by log points since the last call, and clears them.
+    pub fn take_log(&mut self) -> Vec<String> {
+        self.log.drain(..).collect()
+    }
+
+    pub fn add_watchpoint(&mut self, addr: u16, kind: WatchKind) {
+        self.watcher.borrow_mut().add(Watchpoint { addr: addr, kind: kind });
+    }
+
+    /// Removes all watchpoints of `addr`, and returns whether any of them existed.
+    pub fn remove_watchpoint(&mut self, addr: u16) -> bool {
+        self.watcher.borrow_mut().remove(addr)
+    }
+
+    pub fn watchpoints(&self) -> Vec<Watchpoint> {
+        self.watcher.borrow().watchpoints().to_vec()
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.nes.cpu().registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        self.history.clear();
+        &mut self.nes.cpu_mut().registers
+    }
+
+    /// Reads the memory without side effects, the PPU and APU registers are read as the open bus.
+    pub fn read_memory(&mut self, addr: u16, len: usize) -> Vec<u8> {
+        (0..len).map(|i| self.nes.peek(addr.wrapping_add(i as u16))).collect()
+    }
+
+    pub fn write_memory(&mut self, addr: u16, data: u8) {
+        self.history.clear();
+        self.nes.poke(addr, data);
+    }
+
+    /// Disassembles the instruction at `addr`, and returns the text and the byte length.
+    /// The effective address is shown at PC, where the registers resolve it.
+    pub fn disassemble(&mut self, addr: u16) -> (String, u16) {
+        let registers = self.nes.cpu().registers.clone();
+        let mut bus = DebugBus { nes: &mut self.nes };
+        if addr == registers.PC {
+            cpu::disassemble_at_pc(&registers, &mut bus, &*self.symbols)
+        } else {
+            cpu::disassemble_with_labels(&mut bus, addr, &*self.symbols)
+        }
+    }
+
+    /// Executes 1 instruction.
+    pub fn step(&mut self) -> Stop {
+        match self.execute() {
+            Ok((_, Some(hit))) => Stop::Watchpoint(hit),
+            Ok(_) => Stop::Step,
+            Err(stop) => stop,
+        }
+    }
+
+    /// Executes until a breakpoint or a watchpoint.
+    pub fn resume(&mut self) -> Stop {
+        self.run_until(|_, _| false)
+    }
+
+    /// Executes until a breakpoint or a watchpoint like `resume`, but at most `count` instructions.
+    /// Returns `Stop::Step` when all of them have been executed.
+    pub fn resume_for(&mut self, count: usize) -> Stop {
+        let mut executed = 0;
+        self.run_until(|_, _| {
+            executed += 1;
+            executed >= count
+        })
+    }
+
+    /// Executes 1 instruction, but a subroutine called by JSR is executed until it returns.
+    pub fn step_over(&mut self) -> Stop {
+        let pc = self.registers().PC;
+        if self.nes.peek(pc) != JSR {
+            return self.step();
+        }
+
+        let return_addr = pc.wrapping_add(3);
+        let stack = self.registers().S;
+        self.run_until(|debugger, _| {
+            let registers = debugger.registers();
+            registers.PC == return_addr && registers.S == stack
+        })
+    }
+
+    /// Executes until the current subroutine or interrupt handler returns.
+    pub fn step_out(&mut self) -> Stop {
+        let stack = self.registers().S;
+        loop {
+            let opecode = self.nes.peek(self.registers().PC);
+            if let Some(stop) = self.run_once(|_, _| false) {
+                return stop;
+            }
+
+            // the stack is popped above the frame of the current subroutine.
+            if (opecode == RTS || opecode == RTI) && self.registers().S > stack {
+                return Stop::Step;
+            }
+        }
+    }
+
+    /// Executes until all background lines have been built.
+    pub fn run_to_frame(&mut self) -> Stop {
+        match self.run_until(|_, frame| frame) {
+            Stop::Step => Stop::Frame,
+            stop => stop,
+        }
+    }
+
+    /// Restores the state before the last instruction.
+    pub fn step_back(&mut self) -> Stop {
+        if self.position == 0 || !self.travel_to(self.position - 1) {
+            return Stop::HistoryStart;
+        }
+        Stop::Step
+    }
+
+    /// Runs backwards to the last instruction which wrote to `addr`, and stops before it.
+    /// Without such an instruction, it stops at the oldest recorded state.
+    pub fn reverse_to_write(&mut self, addr: u16) -> Stop {
+        let mut end = self.position;
+        while end > 0 {
+            // the instructions between the snapshot and `end` are searched for the write.
+            let start = match self.history.latest_at(end - 1) {
+                Some((start, snapshot)) => {
+                    self.nes.restore(snapshot);
+                    start
+                },
+                None => break,
+            };
+            self.position = start;
+
+            if let Some((position, hit)) = self.replay(end - start, Some(addr)) {
+                self.travel_to(position);
+                return Stop::Watchpoint(hit);
+            }
+            end = start;
+        }
+
+        if let Some(oldest) = self.history.oldest() {
+            self.travel_to(oldest);
+        }
+        Stop::HistoryStart
+    }
+
+    /// Restores the state before the instruction of `position`.
+    /// Returns false when it is older than the history.
+    fn travel_to(&mut self, position: usize) -> bool {
+        let start = match self.history.latest_at(position) {
+            Some((start, snapshot)) => {
+                self.nes.restore(snapshot);
+                start
+            },
+            None => return false,
+        };
+
+        self.position = start;
+        self.replay(position - start, None);
+        true
+    }
+
+    /// Executes `count` instructions again, without the breakpoints and the tracer and the profiler.
+    /// Returns the position and the access of the last instruction which wrote to `watch`.
+    fn replay(&mut self, count: usize, watch: Option<u16>) -> Option<(usize, WatchHit)> {
+        let tracer = self.nes.cpu_mut().take_tracer();
+        let profiler = self.nes.cpu_mut().take_profiler();
+        let watchpoints = watch.into_iter()
+            .map(|addr| Watchpoint { addr: addr, kind: WatchKind::Write })
+            .collect::<Vec<_>>();
+        let watchpoints = self.watcher.borrow_mut().set_watchpoints(watchpoints);
+
+        let mut last_write = None;
+        for _ in 0..count {
+            // errors have been reported when the instructions were executed first.
+            let _ = self.nes.step();
+
+            if let Some(hit) = self.watcher.borrow_mut().take_hit() {
+                last_write = Some((self.position, hit));
+            }
+            self.position += 1;
+        }
+
+        self.watcher.borrow_mut().set_watchpoints(watchpoints);
+        self.nes.cpu_mut().set_tracer(tracer);
+        self.nes.cpu_mut().set_profiler(profiler);
+        last_write
+    }
+
+    /// Executes until `is_done` returns true after an instruction.
+    /// It is called with whether a frame has been built by the instruction.
+    fn run_until<F>(&mut self, mut is_done: F) -> Stop where F: FnMut(&Debugger, bool) -> bool {
+        loop {
+            if let Some(stop) = self.run_once(&mut is_done) {
+                return stop;
+            }
+        }
+    }
+
+    fn run_once<F>(&mut self, mut is_done: F) -> Option<Stop> where F: FnMut(&Debugger, bool) -> bool {
+        let frame = match self.execute() {
+            Ok((_, Some(hit))) => return Some(Stop::Watchpoint(hit)),
+            Ok((frame, None)) => frame,
+            Err(stop) => return Some(stop),
+        };
+
+        // the breakpoint stops before the instruction is executed.
+        let pc = self.registers().PC;
+        if self.hit_breakpoint(pc) {
+            return Some(Stop::Breakpoint(pc));
+        }
+
+        if is_done(self, frame) {
+            return Some(Stop::Step);
+        }
+
+        None
+    }
+
+    /// Returns whether the breakpoint of `pc` stops the execution. Log points are logged here.
+    fn hit_breakpoint(&mut self, pc: u16) -> bool {
+        let breakpoint = match self.breakpoints.get(&pc) {
+            Some(breakpoint) => breakpoint,
+            None => return false,
+        };
+
+        let mut bus = DebugBus { nes: &mut self.nes };
+        if let Some(ref condition) = breakpoint.condition {
+            if !condition.is_true(&mut bus) {
+                return false;
+            }
+        }
+
+        match breakpoint.action {
+            Action::Break => true,
+            Action::Log => {
+                let (text, _) = cpu::disassemble_with_labels(&mut bus, pc, &*self.symbols);
+                let registers = bus.registers();
+                self.log.push(format!(
+                    "{:04X}  {:<31} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X}",
+                    pc, text, registers.A, registers.X, registers.Y, registers.P.to_bit(), registers.S,
+                ));
+                false
+            },
+        }
+    }
+
+    fn execute(&mut self) -> Result<(bool, Option<WatchHit>), Stop> {
+        if self.nes.cpu().state().is_jammed() {
+            return Err(Stop::Jammed);
+        }
+
+        self.history.record(self.position, &self.nes);
+        self.position += 1;
+
+        let frame = self.nes.step();
+        let hit = self.watcher.borrow_mut().take_hit();
+
+        match frame {
+            Ok(frame) => Ok((frame, hit)),
+            Err(error) => Err(Stop::Error(error)),
+        }
+    }
+}
+
+/// The view of `Nes` for the disassembler and expressions, which reads only by peek.
+struct DebugBus<'a> {
+    nes: &'a mut Nes,
+}
+
+impl <'a> Context for DebugBus<'a> {
+    fn registers(&self) -> &Registers {
+        &self.nes.cpu().registers
+    }
+
+    fn read(&mut self, addr: u16) -> u8 {
+        self.nes.peek(addr)
+    }
+
+    fn ppu_position(&self) -> (usize, usize) {
+        (self.nes.ppu().line, self.nes.ppu().cycle)
+    }
+}
+
+impl <'a> cpu::CpuBus for DebugBus<'a> {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.nes.peek(addr)
+    }
+
+    fn read_twice(&mut self, addr: u16) -> u16 {
+        let lower = self.nes.peek(addr) as u16;
+        let upper = self.nes.peek(addr.wrapping_add(1)) as u16;
+        lower | upper << 8
+    }
+
+    fn write(&mut self, _addr: u16, _data: u8) {}
+
+    fn ppu_position(&self) -> (usize, usize) {
+        Context::ppu_position(self)
+    }
+}
+
+#[cfg(test)]
+mod debugger_test {
+    use super::*;
+    use nes::nes_test::nes;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    /// Makes the debugger with `program` at 0x8000, which the reset vector points.
+    pub fn debugger(program: &[u8]) -> Debugger {
+        Debugger::new(nes(program))
+    }
+
+    // 8000: JSR $8010
+    // 8003: STA $0300
+    // 8006: JMP $8006
+    // 8010: LDA #$40
+    // 8012: JSR $8020
+    // 8015: RTS
+    // 8020: INX
+    // 8021: RTS
+    const PROGRAM: [u8; 0x22] = [
+        0x20, 0x10, 0x80, 0x8D, 0x00, 0x03, 0x4C, 0x06, 0x80, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA,
+        0xA9, 0x40, 0x20, 0x20, 0x80, 0x60, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA,
+        0xE8, 0x60,
+    ];
+
+    #[test]
+    fn step_test() {
+        let mut debugger = debugger(&PROGRAM);
+        assert_eq!(debugger.registers().PC, 0x8000);
+        assert_eq!(debugger.step(), Stop::Step);
+        assert_eq!(debugger.registers().PC, 0x8010);
+        assert_eq!(debugger.disassemble(0x8010), (String::from("LDA #$40"), 2));
+    }
+
+    #[test]
+    fn step_over_test() {
+        let mut debugger = debugger(&PROGRAM);
+        assert_eq!(debugger.step_over(), Stop::Step);
+        assert_eq!(debugger.registers().PC, 0x8003);
+        assert_eq!(debugger.registers().A, 0x40);
+        assert_eq!(debugger.registers().X, 0x01);
+    }
+
+    #[test]
+    fn step_out_test() {
+        let mut debugger = debugger(&PROGRAM);
+        debugger.step();
+        debugger.step();
+        debugger.step();
+        assert_eq!(debugger.registers().PC, 0x8020);
+
+        assert_eq!(debugger.step_out(), Stop::Step);
+        assert_eq!(debugger.registers().PC, 0x8015);
+        assert_eq!(debugger.step_out(), Stop::Step);
+        assert_eq!(debugger.registers().PC, 0x8003);
+    }
+
+    #[test]
+    fn breakpoint_test() {
+        let mut debugger = debugger(&PROGRAM);
+        debugger.add_breakpoint(0x8020);
+        assert_eq!(debugger.resume(), Stop::Breakpoint(0x8020));
+        assert_eq!(debugger.registers().A, 0x40);
+
+        debugger.remove_breakpoint(0x8020);
+        debugger.add_breakpoint(0x8006);
+        assert_eq!(debugger.resume(), Stop::Breakpoint(0x8006));
+        assert_eq!(debugger.breakpoints(), vec![0x8006]);
+    }
+
+    #[test]
+    fn conditional_breakpoint_test() {
+        // 8000: INX; JMP $8000
+        let mut debugger = debugger(&[0xE8, 0x4C, 0x00, 0x80]);
+        let condition = Expression::parse("X == 3").unwrap();
+        debugger.set_breakpoint(0x8001, Breakpoint { action: Action::Break, condition: Some(condition) });
+        assert_eq!(debugger.resume(), Stop::Breakpoint(0x8001));
+        assert_eq!(debugger.registers().X, 3);
+    }
+
+    #[test]
+    fn log_point_test() {
+        let mut debugger = debugger(&[0xE8, 0x4C, 0x00, 0x80]);
+        let condition = Expression::parse("X < 3").unwrap();
+        debugger.set_breakpoint(0x8001, Breakpoint { action: Action::Log, condition: Some(condition) });
+        debugger.add_breakpoint(0x8000);
+
+        for _ in 0..3 {
+            assert_eq!(debugger.resume(), Stop::Breakpoint(0x8000));
+        }
+        assert_eq!(debugger.take_log(), vec![
+            "8001  JMP $8000                       A:00 X:01 Y:00 P:34 SP:FD",
+            "8001  JMP $8000                       A:00 X:02 Y:00 P:34 SP:FD",
+        ]);
+        assert!(debugger.take_log().is_empty());
+    }
+
+    #[test]
+    fn symbols_test() {
+        let path = env::temp_dir().join(format!("nes_debugger_symbols_test_{}.0.nl", process::id()));
+        fs::write(&path, "$8010#sub#\n$8020#inner#\n").unwrap();
+
+        let mut debugger = debugger(&PROGRAM);
+        debugger.load_symbols(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(debugger.disassemble(0x8000), (String::from("JSR sub"), 3));
+        assert_eq!(debugger.resolve_addr("inner"), Some(0x8020));
+        assert_eq!(debugger.resolve_addr("$8000"), Some(0x8000));
+        assert_eq!(debugger.resolve_addr("nothing"), None);
+        assert!(debugger.load_symbols("nothing.nl").is_err());
+    }
+
+    #[test]
+    fn resume_for_test() {
+        let mut debugger = debugger(&PROGRAM);
+        assert_eq!(debugger.resume_for(2), Stop::Step);
+        assert_eq!(debugger.registers().PC, 0x8012);
+
+        debugger.add_breakpoint(0x8020);
+        assert_eq!(debugger.resume_for(100), Stop::Breakpoint(0x8020));
+    }
+
+    #[test]
+    fn watchpoint_test() {
+        let mut debugger = debugger(&PROGRAM);
+        debugger.add_watchpoint(0x0300, WatchKind::Write);
+        let hit = WatchHit { addr: 0x0300, data: 0x40, write: true, kind: WatchKind::Write };
+        assert_eq!(debugger.resume(), Stop::Watchpoint(hit));
+        assert_eq!(debugger.registers().PC, 0x8006);
+        assert_eq!(debugger.read_memory(0x0300, 2), vec![0x40, 0x00]);
+
+        assert!(debugger.remove_watchpoint(0x0300));
+        assert!(!debugger.remove_watchpoint(0x0300));
+    }
+
+    #[test]
+    fn run_to_frame_test() {
+        let mut debugger = debugger(&PROGRAM);
+        assert_eq!(debugger.run_to_frame(), Stop::Frame);
+        assert_eq!(debugger.registers().PC, 0x8006);
+    }
+
+    #[test]
+    fn step_back_test() {
+        let mut debugger = debugger(&PROGRAM);
+        assert_eq!(debugger.step_back(), Stop::HistoryStart);
+
+        // until INX in the nested subroutine
+        for _ in 0..4 {
+            debugger.step();
+        }
+        assert_eq!(debugger.registers().X, 0x01);
+
+        assert_eq!(debugger.step_back(), Stop::Step);
+        assert_eq!(debugger.position(), 3);
+        assert_eq!(debugger.registers().PC, 0x8020);
+        assert_eq!(debugger.registers().X, 0x00);
+        assert_eq!(debugger.registers().S, 0xF9);
+
+        // the execution continues from the restored state.
+        debugger.step();
+        assert_eq!(debugger.registers().X, 0x01);
+    }
+
+    #[test]
+    fn reverse_to_write_test() {
+        // LDA #$01; STA $0300; LDA #$02; STA $0300; NOP
+        let mut debugger = debugger(&[0xA9, 0x01, 0x8D, 0x00, 0x03, 0xA9, 0x02, 0x8D, 0x00, 0x03]);
+        for _ in 0..2500 {
+            debugger.step();
+        }
+
+        // the write is in the older interval of the snapshots.
+        let hit = WatchHit { addr: 0x0300, data: 0x02, write: true, kind: WatchKind::Write };
+        assert_eq!(debugger.reverse_to_write(0x0300), Stop::Watchpoint(hit));
+        assert_eq!(debugger.position(), 3);
+        assert_eq!(debugger.registers().PC, 0x8007);
+        assert_eq!(debugger.read_memory(0x0300, 1), vec![0x01]);
+
+        assert_eq!(debugger.reverse_to_write(0x0200), Stop::HistoryStart);
+        assert_eq!(debugger.position(), 0);
+        assert_eq!(debugger.registers().PC, 0x8000);
+
+        // the state changed by hand can't be replayed.
+        debugger.step();
+        debugger.write_memory(0x0300, 0xFF);
+        assert_eq!(debugger.step_back(), Stop::HistoryStart);
+    }
+
+    #[test]
+    fn code_data_log_test() {
+        use nes::cdl::{self, CodeDataLog};
+        use std::cell::RefCell;
+
+        // LDA $8010
+        let mut debugger = debugger(&[0xAD, 0x10, 0x80]);
+        let code_data_log = Rc::new(RefCell::new(CodeDataLog::new(0x4000, 0x2000)));
+        debugger.nes_mut().set_code_data_log(Some(code_data_log.clone()));
+        debugger.step();
+
+        assert_eq!(&code_data_log.borrow().prg()[..4], &[cdl::CODE, cdl::CODE, cdl::CODE, 0x00]);
+        assert_eq!(code_data_log.borrow().prg()[0x10], cdl::DATA);
+
+        // the name table is cleared, the pattern 0 is rendered everywhere while the background is shown.
+        debugger.nes_mut().poke(0x2001, 0x08);
+        debugger.run_to_frame();
+        assert_eq!(code_data_log.borrow().chr()[0x0F], cdl::RENDERED);
+        assert_eq!(code_data_log.borrow().chr()[0x10], 0x00);
+    }
+}
@@ -0,0 +1,368 @@
+use std::cmp;
+use std::io::{self, BufRead, Write};
+
+use nes::cpu::{Labels, Profiler};
+use super::{Action, Breakpoint, Debugger, Expression, Stop, WatchKind};
+
+// the whole address space of the CPU.
+const MAX_DUMP_LENGTH: usize = 0x10000;
+
+const HELP: &str = "\
+s, step             execute 1 instruction
+n, next             step over JSR
+o, out              run until the current subroutine returns
+c, continue         run until a breakpoint or a watchpoint
+f, frame            run until the next frame
+rs, rstep           step back 1 instruction
+rw, rwrite ADDR     run backwards to the last write to ADDR
+b, break ADDR [if COND]
+                    set a breakpoint, which stops only when COND holds
+l, log ADDR [if COND]
+                    set a log point, which logs the registers and continues
+d, delete ADDR      delete the breakpoint
+w, watch ADDR [r|w] watch reads, writes or both of ADDR
+u, unwatch ADDR     delete the watchpoints of ADDR
+r, regs             show the registers
+x ADDR [LEN]        dump the memory
+dis ADDR [N]        disassemble N instructions from ADDR
+p, profile [on|off] start or stop the profiler, or show the cycles of the subroutines
+q, quit             quit the debugger";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Step,
+    Next,
+    Out,
+    Continue,
+    Frame,
+    ReverseStep,
+    ReverseWrite(u16),
+    Break(u16, Action, Option<Expression>),
+    Delete(u16),
+    Watch(u16, WatchKind),
+    Unwatch(u16),
+    Registers,
+    Examine(u16, usize),
+    Disassemble(u16, usize),
+    /// Starts or stops the profiler, or shows the report without the argument.
+    Profile(Option<bool>),
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Addresses are hexadecimal or labels of the loaded symbols.
+    fn parse(line: &str, debugger: &Debugger) -> Result<Command, String> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let command = match words.first() {
+            Some(command) => *command,
+            None => return Err(String::from("no command")),
+        };
+        let args = &words[1..];
+
+        match command {
+            "s" | "step" => Ok(Command::Step),
+            "n" | "next" => Ok(Command::Next),
+            "o" | "out" => Ok(Command::Out),
+            "c" | "continue" => Ok(Command::Continue),
+            "f" | "frame" => Ok(Command::Frame),
+            "rs" | "rstep" => Ok(Command::ReverseStep),
+            "rw" | "rwrite" => Ok(Command::ReverseWrite(parse_addr(args.get(0), debugger)?)),
+            "b" | "break" => parse_breakpoint(args, Action::Break, debugger),
+            "l" | "log" => parse_breakpoint(args, Action::Log, debugger),
+            "d" | "delete" => Ok(Command::Delete(parse_addr(args.get(0), debugger)?)),
+            "w" | "watch" => {
+                let kind = match args.get(1) {
+                    None => WatchKind::Access,
+                    Some(&"r") => WatchKind::Read,
+                    Some(&"w") => WatchKind::Write,
+                    Some(kind) => return Err(format!("unknown watch kind: {}", kind)),
+                };
+                Ok(Command::Watch(parse_addr(args.get(0), debugger)?, kind))
+            },
+            "u" | "unwatch" => Ok(Command::Unwatch(parse_addr(args.get(0), debugger)?)),
+            "r" | "regs" => Ok(Command::Registers),
+            "x" => {
+                let len = match args.get(1) {
+                    None => 16,
+                    Some(len) => len.parse().map_err(|_| format!("invalid length: {}", len))?,
+                };
+                Ok(Command::Examine(parse_addr(args.get(0), debugger)?, len))
+            },
+            "dis" => {
+                let count = match args.get(1) {
+                    None => 10,
+                    Some(count) => count.parse().map_err(|_| format!("invalid count: {}", count))?,
+                };
+                Ok(Command::Disassemble(parse_addr(args.get(0), debugger)?, count))
+            },
+            "p" | "profile" => match args.get(0) {
+                None => Ok(Command::Profile(None)),
+                Some(&"on") => Ok(Command::Profile(Some(true))),
+                Some(&"off") => Ok(Command::Profile(Some(false))),
+                Some(arg) => Err(format!("unexpected {}", arg)),
+            },
+            "h" | "help" => Ok(Command::Help),
+            "q" | "quit" => Ok(Command::Quit),
+            _ => Err(format!("unknown command: {}", command)),
+        }
+    }
+}
+
+/// Parses `ADDR [if COND]`.
+fn parse_breakpoint(args: &[&str], action: Action, debugger: &Debugger) -> Result<Command, String> {
+    let addr = parse_addr(args.get(0), debugger)?;
+    let condition = match args.get(1) {
+        None => None,
+        Some(&"if") => Some(Expression::parse(&args[2..].join(" "))?),
+        Some(arg) => return Err(format!("unexpected {}", arg)),
+    };
+    Ok(Command::Break(addr, action, condition))
+}
+
+fn parse_addr(arg: Option<&&str>, debugger: &Debugger) -> Result<u16, String> {
+    let arg = match arg {
+        Some(arg) => *arg,
+        None => return Err(String::from("address is required")),
+    };
+
+    debugger.resolve_addr(arg).ok_or_else(|| format!("invalid address: {}", arg))
+}
+
+/// Reads commands from `input` until it ends or "quit" is entered.
+pub fn run<R: BufRead, W: Write>(debugger: &mut Debugger, input: R, output: &mut W) -> io::Result<()> {
+    print_location(debugger, output)?;
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let command = match Command::parse(&line, debugger) {
+            Ok(command) => command,
+            Err(message) => {
+                writeln!(output, "{}", message)?;
+                continue;
+            },
+        };
+
+        let stop = match command {
+            Command::Step => debugger.step(),
+            Command::Next => debugger.step_over(),
+            Command::Out => debugger.step_out(),
+            Command::Continue => debugger.resume(),
+            Command::Frame => debugger.run_to_frame(),
+            Command::ReverseStep => debugger.step_back(),
+            Command::ReverseWrite(addr) => debugger.reverse_to_write(addr),
+            Command::Break(addr, action, condition) => {
+                debugger.set_breakpoint(addr, Breakpoint { action: action, condition: condition });
+                continue;
+            },
+            Command::Delete(addr) => {
+                if !debugger.remove_breakpoint(addr) {
+                    writeln!(output, "no breakpoint at ${:04X}", addr)?;
+                }
+                continue;
+            },
+            Command::Watch(addr, kind) => {
+                debugger.add_watchpoint(addr, kind);
+                continue;
+            },
+            Command::Unwatch(addr) => {
+                if !debugger.remove_watchpoint(addr) {
+                    writeln!(output, "no watchpoint at ${:04X}", addr)?;
+                }
+                continue;
+            },
+            Command::Registers => {
+                print_registers(debugger, output)?;
+                continue;
+            },
+            Command::Examine(addr, len) => {
+                print_memory(debugger, addr, len, output)?;
+                continue;
+            },
+            Command::Disassemble(addr, count) => {
+                print_disassembly(debugger, addr, count, output)?;
+                continue;
+            },
+            Command::Profile(Some(on)) => {
+                let profiler = if on { Some(Profiler::new()) } else { None };
+                debugger.nes_mut().cpu_mut().set_profiler(profiler);
+                continue;
+            },
+            Command::Profile(None) => {
+                match debugger.nes().cpu().profile_report() {
+                    Some(report) => writeln!(output, "{}", report)?,
+                    None => writeln!(output, "the profiler is off")?,
+                }
+                continue;
+            },
+            Command::Help => {
+                writeln!(output, "{}", HELP)?;
+                continue;
+            },
+            Command::Quit => break,
+        };
+
+        for line in debugger.take_log() {
+            writeln!(output, "{}", line)?;
+        }
+        print_stop(stop, output)?;
+        print_location(debugger, output)?;
+    }
+
+    Ok(())
+}
+
+fn print_stop<W: Write>(stop: Stop, output: &mut W) -> io::Result<()> {
+    match stop {
+        Stop::Step => Ok(()),
+        Stop::Breakpoint(addr) => writeln!(output, "breakpoint at ${:04X}", addr),
+        Stop::Watchpoint(hit) => {
+            let access = if hit.write { "write" } else { "read" };
+            writeln!(output, "watchpoint: {} ${:02X} at ${:04X}", access, hit.data, hit.addr)
+        },
+        Stop::Frame => writeln!(output, "frame"),
+        Stop::Jammed => writeln!(output, "the CPU is jammed"),
+        Stop::HistoryStart => writeln!(output, "the oldest recorded state"),
+        Stop::Error(error) => writeln!(output, "cpu error: {:?}", error),
+    }
+}
+
+fn print_location<W: Write>(debugger: &mut Debugger, output: &mut W) -> io::Result<()> {
+    let pc = debugger.registers().PC;
+    if let Some(label) = debugger.symbols().label(pc) {
+        writeln!(output, "{}:", label)?;
+    }
+
+    let (text, _) = debugger.disassemble(pc);
+    match debugger.symbols().source_line(pc) {
+        Some(source) => writeln!(output, "{:04X}  {:<24}; {}:{}", pc, text, source.file, source.line),
+        None => writeln!(output, "{:04X}  {}", pc, text),
+    }
+}
+
+fn print_registers<W: Write>(debugger: &Debugger, output: &mut W) -> io::Result<()> {
+    let registers = debugger.registers();
+    writeln!(
+        output,
+        "PC:{:04X} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X}",
+        registers.PC, registers.A, registers.X, registers.Y, registers.P.to_bit(), registers.S,
+    )
+}
+
+fn print_memory<W: Write>(debugger: &mut Debugger, addr: u16, len: usize, output: &mut W) -> io::Result<()> {
+    let memory = debugger.read_memory(addr, cmp::min(len, MAX_DUMP_LENGTH));
+    for (i, row) in memory.chunks(16).enumerate() {
+        let bytes = row.iter().map(|data| format!("{:02X}", data)).collect::<Vec<_>>();
+        writeln!(output, "{:04X}  {}", addr.wrapping_add((i as u16).wrapping_mul(16)), bytes.join(" "))?;
+    }
+    Ok(())
+}
+
+fn print_disassembly<W: Write>(debugger: &mut Debugger, addr: u16, count: usize, output: &mut W) -> io::Result<()> {
+    let mut addr = addr;
+    // an instruction is 1 byte at least.
+    for _ in 0..cmp::min(count, MAX_DUMP_LENGTH) {
+        if let Some(label) = debugger.symbols().label(addr) {
+            writeln!(output, "{}:", label)?;
+        }
+        let (text, length) = debugger.disassemble(addr);
+        writeln!(output, "{:04X}  {}", addr, text)?;
+        addr = addr.wrapping_add(length);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod repl_test {
+    use super::*;
+    use super::super::debugger_test::debugger;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn parse_test() {
+        let debugger = debugger(&[]);
+        let parse = |line| Command::parse(line, &debugger);
+        assert_eq!(parse("s"), Ok(Command::Step));
+        assert_eq!(parse("break $8000"), Ok(Command::Break(0x8000, Action::Break, None)));
+        assert_eq!(parse("b 0x8000"), Ok(Command::Break(0x8000, Action::Break, None)));
+        let condition = Expression::parse("A == $40 && [$0300] > 3").ok();
+        assert_eq!(parse("l 8000 if A == $40 && [$0300] > 3"), Ok(Command::Break(0x8000, Action::Log, condition)));
+        assert!(parse("b 8000 if").is_err());
+        assert!(parse("b 8000 when A").is_err());
+        assert_eq!(parse("w 300 r"), Ok(Command::Watch(0x0300, WatchKind::Read)));
+        assert_eq!(parse("rw $0300"), Ok(Command::ReverseWrite(0x0300)));
+        assert_eq!(parse("w 300"), Ok(Command::Watch(0x0300, WatchKind::Access)));
+        assert_eq!(parse("x 0 4"), Ok(Command::Examine(0x0000, 4)));
+        assert_eq!(parse("dis 8000 3"), Ok(Command::Disassemble(0x8000, 3)));
+        assert_eq!(parse("dis 8000"), Ok(Command::Disassemble(0x8000, 10)));
+        assert!(parse("dis 8000 many").is_err());
+        assert_eq!(parse("p on"), Ok(Command::Profile(Some(true))));
+        assert_eq!(parse("profile"), Ok(Command::Profile(None)));
+        assert!(parse("p now").is_err());
+        assert!(parse("b").is_err());
+        assert!(parse("b xyz").is_err());
+        assert!(parse("jump").is_err());
+    }
+
+    #[test]
+    fn run_with_symbols_test() {
+        let path = env::temp_dir().join(format!("nes_repl_symbols_test_{}.0.nl", process::id()));
+        fs::write(&path, "$8000#reset#\n$8002#store#\n$0300#buffer#\n").unwrap();
+
+        // LDA #$40; STA $0300
+        let mut debugger = debugger(&[0xA9, 0x40, 0x8D, 0x00, 0x03]);
+        debugger.load_symbols(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        let input = "b store\nc\n";
+        let mut output = vec![];
+        run(&mut debugger, input.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "\
+reset:
+8000  LDA #$40
+breakpoint at $8002
+store:
+8002  STA buffer
+");
+    }
+
+    #[test]
+    fn print_memory_test() {
+        let mut debugger = debugger(&[]);
+        let mut output = vec![];
+        print_memory(&mut debugger, 0xFFF0, 0x20000, &mut output).unwrap();
+
+        // the dump wraps around, and it is clamped to the address space.
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 0x1000);
+        assert!(lines[1].starts_with("0000  00 00"));
+        assert!(lines[0x0FFF].starts_with("FFE0  "));
+    }
+
+    #[test]
+    fn run_test() {
+        // LDA #$40; STA $0300
+        let mut debugger = debugger(&[0xA9, 0x40, 0x8D, 0x00, 0x03]);
+        let input = "s\nw $0300 w\nc\nx 300 2\ndis 8000 3\nq\ns\n";
+        let mut output = vec![];
+        run(&mut debugger, input.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "\
+8000  LDA #$40
+8002  STA $0300
+watchpoint: write $40 at $0300
+8005  NOP
+0300  40 00
+8000  LDA #$40
+8002  STA $0300
+8005  NOP
+");
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+use nes::cpu::Labels;
+
+const PROGRAM_BANK_SIZE: usize = 0x4000;
+const INES_HEADER_SIZE: usize = 0x0010;
+
+/// Where a symbol is.
+/// `bank` is the 16KB program rom bank for 0x8000-0xFFFF, and None for the other addresses.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Location {
+    pub bank: Option<usize>,
+    pub addr: u16,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SourceLine {
+    pub file: String,
+    pub line: usize,
+}
+
+/// Labels and source lines loaded from ca65 `.dbg` files and FCEUX `.nl` files.
+#[derive(Clone)]
+pub struct Symbols {
+    program_banks: usize,
+    labels: HashMap<Location, String>,
+    locations: HashMap<String, Location>,
+    source_lines: HashMap<Location, SourceLine>,
+}
+
+#[derive(Debug)]
+pub enum SymbolError {
+    IoError(io::Error),
+    /// The line of the file can't be parsed.
+    FormatError { line: usize },
+}
+
+impl From<io::Error> for SymbolError {
+    fn from(err: io::Error) -> Self {
+        SymbolError::IoError(err)
+    }
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SymbolError::IoError(ref err) => write!(f, "{}", err),
+            SymbolError::FormatError { line } => write!(f, "line {} can't be parsed", line),
+        }
+    }
+}
+
+impl Symbols {
+    /// `program_banks` is the number of 16KB banks of the program rom, which are mapped to 0x8000-0xFFFF.
+    pub fn new(program_banks: usize) -> Self {
+        Self {
+            program_banks: program_banks,
+            labels: HashMap::new(),
+            locations: HashMap::new(),
+            source_lines: HashMap::new(),
+        }
+    }
+
+    /// Returns the location which `addr` of the CPU is mapped to.
+    pub fn location(&self, addr: u16) -> Location {
+        if addr < 0x8000 || self.program_banks == 0 {
+            return Location { bank: None, addr: addr };
+        }
+
+        // 16KB program rom is mirrored to 0xC000-0xFFFF.
+        let bank = (addr as usize - 0x8000) / PROGRAM_BANK_SIZE % self.program_banks;
+        Location { bank: Some(bank), addr: addr }
+    }
+
+    pub fn label_at(&self, location: Location) -> Option<&str> {
+        self.labels.get(&location).map(|label| label.as_str())
+    }
+
+    pub fn location_of(&self, label: &str) -> Option<Location> {
+        self.locations.get(label).cloned()
+    }
+
+    pub fn source_line(&self, addr: u16) -> Option<&SourceLine> {
+        self.source_lines.get(&self.location(addr))
+    }
+
+    fn add_label(&mut self, location: Location, label: &str) {
+        self.labels.insert(location, String::from(label));
+        self.locations.insert(String::from(label), location);
+    }
+
+    /// Loads the file by its extension, `.dbg` or `.nl`.
+    /// The bank of `.nl` is taken from the file name like FCEUX, `game.nes.1.nl` or `game.nes.ram.nl`.
+    pub fn load_file(&mut self, path: &str) -> Result<(), SymbolError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+
+        let path = Path::new(path);
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("dbg") => self.load_dbg(&text),
+            _ => {
+                let bank = path.file_stem()
+                    .and_then(|stem| Path::new(stem).extension())
+                    .and_then(|bank| usize::from_str_radix(&bank.to_string_lossy(), 16).ok());
+                self.load_nl(&text, bank)
+            },
+        }
+    }
+
+    /// Loads FCEUX `.nl`, whose line is `$C000#label#comment` or `$0300/10#array#comment`.
+    /// `bank` is the program rom bank of the file, None for the RAM file.
+    pub fn load_nl(&mut self, text: &str, bank: Option<usize>) -> Result<(), SymbolError> {
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = SymbolError::FormatError { line: i + 1 };
+            let mut fields = line.splitn(3, '#');
+            let addr = fields.next().unwrap().split('/').next().unwrap();
+            let label = match fields.next() {
+                Some(label) => label,
+                None => return Err(error),
+            };
+            let addr = match parse_number(addr) {
+                Some(addr) if addr <= 0xFFFF => addr as u16,
+                _ => return Err(error),
+            };
+
+            // lines without the label have only the comment.
+            if label.is_empty() {
+                continue;
+            }
+
+            let bank = if addr < 0x8000 { None } else { bank };
+            self.add_label(Location { bank: bank, addr: addr }, label);
+        }
+
+        Ok(())
+    }
+
+    /// Loads ca65 `.dbg`, whose line is the record type and `key=value` pairs separated by ','.
+    pub fn load_dbg(&mut self, text: &str) -> Result<(), SymbolError> {
+        let mut files = HashMap::new();
+        let mut segments = HashMap::new();
+        let mut spans = HashMap::new();
+        let mut lines = vec![];
+        let mut symbols = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let mut fields = line.splitn(2, '\t');
+            let record_type = fields.next().unwrap().trim();
+            let record = parse_record(fields.next().unwrap_or(""));
+            let error = || SymbolError::FormatError { line: i + 1 };
+            let number = |key: &str| record.get(key).and_then(|value| parse_number(value));
+            let id = || number("id").ok_or_else(error);
+
+            match record_type {
+                "file" => {
+                    let name = record.get("name").ok_or_else(error)?;
+                    files.insert(id()?, name.clone());
+                },
+                "seg" => {
+                    let start = number("start").ok_or_else(error)?;
+                    segments.insert(id()?, (start, number("ooffs")));
+                },
+                "span" => {
+                    let segment = number("seg").ok_or_else(error)?;
+                    let start = number("start").ok_or_else(error)?;
+                    spans.insert(id()?, (segment, start));
+                },
+                // macro expansions (type=2) point the macro definition, they aren't the source line.
+                "line" if number("type") != Some(2) => {
+                    let file = number("file").ok_or_else(error)?;
+                    let line_number = number("line").ok_or_else(error)?;
+                    // a line without span doesn't generate any code.
+                    if let Some(span_ids) = record.get("span") {
+                        for span in span_ids.split('+') {
+                            let span = parse_number(span).ok_or_else(error)?;
+                            lines.push((file, line_number, span));
+                        }
+                    }
+                },
+                "sym" if record.get("type").map(|t| t.as_str()) == Some("lab") => {
+                    let name = record.get("name").ok_or_else(error)?;
+                    let value = number("val").ok_or_else(error)?;
+                    symbols.push((name.clone(), value, number("seg")));
+                },
+                _ => {},
+            }
+        }
+
+        for (name, value, segment) in symbols {
+            let segment = segment.and_then(|segment| segments.get(&segment)).cloned();
+            let location = self.dbg_location(value as u16, segment);
+            self.add_label(location, &name);
+        }
+
+        for (file, line, span) in lines {
+            let (segment, offset) = match spans.get(&span) {
+                Some(&span) => span,
+                None => continue,
+            };
+            let segment = match segments.get(&segment) {
+                Some(&segment) => segment,
+                None => continue,
+            };
+            let name = match files.get(&file) {
+                Some(name) => name.clone(),
+                None => continue,
+            };
+
+            let location = self.dbg_location((segment.0 + offset) as u16, Some(segment));
+            self.source_lines.entry(location).or_insert(SourceLine { file: name, line: line });
+        }
+
+        Ok(())
+    }
+
+    /// The location of the address in the segment of `(start, output offset)`.
+    /// The segment is written at the output offset of the iNES file, and it can span several banks.
+    fn dbg_location(&self, addr: u16, segment: Option<(usize, Option<usize>)>) -> Location {
+        match segment {
+            Some((start, Some(output_offset))) if addr >= 0x8000 && addr as usize >= start && output_offset >= INES_HEADER_SIZE => {
+                let offset = output_offset + (addr as usize - start) - INES_HEADER_SIZE;
+                Location { bank: Some(offset / PROGRAM_BANK_SIZE), addr: addr }
+            },
+            _ => self.location(addr),
+        }
+    }
+}
+
+impl Labels for Symbols {
+    fn label(&self, addr: u16) -> Option<&str> {
+        self.label_at(self.location(addr))
+    }
+}
+
+/// Parses `key=value,key="value"` into the map. The quotes are removed.
+fn parse_record(text: &str) -> HashMap<String, String> {
+    let mut record = HashMap::new();
+    let mut rest = text.trim();
+
+    while !rest.is_empty() {
+        let equal = match rest.find('=') {
+            Some(equal) => equal,
+            None => break,
+        };
+        let key = rest[..equal].trim();
+        rest = &rest[equal + 1..];
+
+        let value = if rest.starts_with('"') {
+            let end = rest[1..].find('"').map_or(rest.len(), |end| end + 1);
+            let value = &rest[1..end];
+            rest = rest.get(end + 1..).unwrap_or("");
+            value
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let value = &rest[..end];
+            rest = &rest[end..];
+            value
+        };
+
+        record.insert(String::from(key), String::from(value));
+        rest = rest.trim_start_matches(',');
+    }
+
+    record
+}
+
+/// Parses "$C000", "0xC000" or decimal.
+fn parse_number(text: &str) -> Option<usize> {
+    let text = text.trim();
+    if text.starts_with('$') {
+        usize::from_str_radix(&text[1..], 16).ok()
+    } else if text.starts_with("0x") {
+        usize::from_str_radix(&text[2..], 16).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod symbols_test {
+    use super::*;
+
+    #[test]
+    fn load_nl_test() {
+        let mut symbols = Symbols::new(2);
+        let nl = "$C000#reset#the entry point\n$C010##only comment\n\n$0300/10#buffer#\n";
+        symbols.load_nl(nl, Some(1)).unwrap();
+
+        assert_eq!(symbols.label(0xC000), Some("reset"));
+        assert_eq!(symbols.label(0xC010), None);
+        assert_eq!(symbols.label(0x0300), Some("buffer"));
+        assert_eq!(symbols.location_of("reset"), Some(Location { bank: Some(1), addr: 0xC000 }));
+
+        assert!(symbols.load_nl("C000#reset#", Some(0)).is_err());
+        match symbols.load_nl("$C000#ok#\n$C000", Some(0)) {
+            Err(error @ SymbolError::FormatError { line: 2 }) => assert_eq!(error.to_string(), "line 2 can't be parsed"),
+            result => panic!("unexpected {:?}", result),
+        }
+    }
+
+    #[test]
+    fn location_test() {
+        // 16KB program rom is mirrored.
+        let symbols = Symbols::new(1);
+        assert_eq!(symbols.location(0x8000), Location { bank: Some(0), addr: 0x8000 });
+        assert_eq!(symbols.location(0xC000), Location { bank: Some(0), addr: 0xC000 });
+        assert_eq!(symbols.location(0x0300), Location { bank: None, addr: 0x0300 });
+
+        let symbols = Symbols::new(2);
+        assert_eq!(symbols.location(0xC000), Location { bank: Some(1), addr: 0xC000 });
+    }
+
+    #[test]
+    fn load_dbg_test() {
+        let dbg = "\
+version\tmajor=2,minor=0
+file\tid=0,name=\"hello, world.s\",size=100,mtime=0x5B000000,mod=0
+seg\tid=0,name=\"CODE\",start=0x00C000,size=0x0010,addrsize=absolute,type=ro,oname=\"hello.nes\",ooffs=16400
+seg\tid=1,name=\"BSS\",start=0x000300,size=0x0010,addrsize=absolute,type=rw
+span\tid=0,seg=0,start=0,size=2
+span\tid=1,seg=0,start=2,size=3
+line\tid=0,file=0,line=10,span=0
+line\tid=1,file=0,line=11,span=1
+line\tid=2,file=0,line=3,type=2,span=1
+sym\tid=0,name=\"reset\",addrsize=absolute,scope=0,def=0,val=0xC000,seg=0,type=lab
+sym\tid=1,name=\"buffer\",addrsize=absolute,scope=0,def=0,val=0x300,seg=1,type=lab
+sym\tid=2,name=\"SIZE\",addrsize=zeropage,scope=0,def=0,val=0x10,type=equ
+";
+        let mut symbols = Symbols::new(2);
+        symbols.load_dbg(dbg).unwrap();
+
+        assert_eq!(symbols.label(0xC000), Some("reset"));
+        assert_eq!(symbols.label(0x0300), Some("buffer"));
+        assert_eq!(symbols.label(0x0010), None);
+        assert_eq!(symbols.location_of("reset"), Some(Location { bank: Some(1), addr: 0xC000 }));
+
+        let line = SourceLine { file: String::from("hello, world.s"), line: 11 };
+        assert_eq!(symbols.source_line(0xC002), Some(&line));
+        assert_eq!(symbols.source_line(0xC001), None);
+    }
+
+    #[test]
+    fn load_dbg_crossing_banks_test() {
+        // NROM-256: CODE starts at $8000 in the bank 0, and continues to the bank 1.
+        let dbg = "\
+file\tid=0,name=\"main.s\",size=100,mtime=0x5B000000,mod=0
+seg\tid=0,name=\"CODE\",start=0x008000,size=0x7FFA,addrsize=absolute,type=ro,oname=\"game.nes\",ooffs=16
+span\tid=0,seg=0,start=0x4123,size=3
+line\tid=0,file=0,line=20,span=0
+sym\tid=0,name=\"main\",addrsize=absolute,scope=0,def=0,val=0x8000,seg=0,type=lab
+sym\tid=1,name=\"update\",addrsize=absolute,scope=0,def=0,val=0xC123,seg=0,type=lab
+";
+        let mut symbols = Symbols::new(2);
+        symbols.load_dbg(dbg).unwrap();
+
+        assert_eq!(symbols.location_of("main"), Some(Location { bank: Some(0), addr: 0x8000 }));
+        assert_eq!(symbols.location_of("update"), Some(Location { bank: Some(1), addr: 0xC123 }));
+        assert_eq!(symbols.label(0xC123), Some("update"));
+        assert_eq!(symbols.source_line(0xC123), Some(&SourceLine { file: String::from("main.s"), line: 20 }));
+    }
+
+    #[test]
+    fn parse_record_test() {
+        let record = parse_record("id=0,name=\"a,b\",size=3");
+        assert_eq!(record.get("id").map(|v| v.as_str()), Some("0"));
+        assert_eq!(record.get("name").map(|v| v.as_str()), Some("a,b"));
+        assert_eq!(record.get("size").map(|v| v.as_str()), Some("3"));
+    }
+}
@@ -0,0 +1,125 @@
+use std::mem;
+
+use nes::cpu::{Access, AccessKind, BusObserver};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WatchKind {
+    Read,
+    Write,
+    /// Both of read and write.
+    Access,
+}
+
+impl WatchKind {
+    fn matches(&self, write: bool) -> bool {
+        match *self {
+            WatchKind::Read => !write,
+            WatchKind::Write => write,
+            WatchKind::Access => true,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Watchpoint {
+    pub addr: u16,
+    pub kind: WatchKind,
+}
+
+/// An access which a watchpoint caught.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WatchHit {
+    pub addr: u16,
+    pub data: u8,
+    pub write: bool,
+    /// The kind of the watchpoint which caught the access.
+    pub kind: WatchKind,
+}
+
+/// Catches the accesses to the watched addresses by observing the bus.
+/// Fetches of instructions are caught as reads.
+pub struct Watcher {
+    watchpoints: Vec<Watchpoint>,
+    hit: Option<WatchHit>,
+}
+
+impl Watcher {
+    pub fn new(watchpoints: Vec<Watchpoint>) -> Self {
+        Self {
+            watchpoints: watchpoints,
+            hit: None,
+        }
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    /// Replaces the watchpoints, and returns the old ones.
+    pub fn set_watchpoints(&mut self, watchpoints: Vec<Watchpoint>) -> Vec<Watchpoint> {
+        mem::replace(&mut self.watchpoints, watchpoints)
+    }
+
+    pub fn add(&mut self, watchpoint: Watchpoint) {
+        if !self.watchpoints.contains(&watchpoint) {
+            self.watchpoints.push(watchpoint);
+        }
+    }
+
+    /// Removes all watchpoints of `addr`, and returns whether any of them existed.
+    pub fn remove(&mut self, addr: u16) -> bool {
+        let len = self.watchpoints.len();
+        self.watchpoints.retain(|w| w.addr != addr);
+        self.watchpoints.len() != len
+    }
+
+    /// Returns the first caught access since the last call, and clears it.
+    pub fn take_hit(&mut self) -> Option<WatchHit> {
+        self.hit.take()
+    }
+}
+
+impl BusObserver for Watcher {
+    fn observe(&mut self, access: &Access) {
+        if self.hit.is_some() {
+            return;
+        }
+
+        let write = access.kind == AccessKind::Write;
+        if let Some(watchpoint) = self.watchpoints.iter().find(|w| w.addr == access.addr && w.kind.matches(write)) {
+            self.hit = Some(WatchHit { addr: access.addr, data: access.data, write: write, kind: watchpoint.kind });
+        }
+    }
+}
+
+#[cfg(test)]
+mod watcher_test {
+    use super::*;
+
+    fn access(addr: u16, data: u8, kind: AccessKind) -> Access {
+        Access { addr: addr, data: data, kind: kind, cycle: 0 }
+    }
+
+    #[test]
+    fn observe_test() {
+        let mut watcher = Watcher::new(vec![Watchpoint { addr: 0x0011, kind: WatchKind::Read }]);
+        watcher.add(Watchpoint { addr: 0x0020, kind: WatchKind::Write });
+        watcher.add(Watchpoint { addr: 0x0020, kind: WatchKind::Write });
+        assert_eq!(watcher.watchpoints().len(), 2);
+
+        watcher.observe(&access(0x0011, 0x34, AccessKind::Write));
+        watcher.observe(&access(0x0020, 0x00, AccessKind::Read));
+        assert_eq!(watcher.take_hit(), None);
+
+        // only the first access is kept.
+        watcher.observe(&access(0x0011, 0x34, AccessKind::Fetch));
+        watcher.observe(&access(0x0020, 0x56, AccessKind::Write));
+        assert_eq!(watcher.take_hit(), Some(WatchHit { addr: 0x0011, data: 0x34, write: false, kind: WatchKind::Read }));
+        assert_eq!(watcher.take_hit(), None);
+
+        assert!(watcher.remove(0x0011));
+        assert!(!watcher.remove(0x0011));
+        watcher.observe(&access(0x0011, 0x34, AccessKind::Read));
+        assert_eq!(watcher.take_hit(), None);
+    }
+}
@@ -1,14 +1,21 @@
 pub mod cassette;
+pub mod cdl;
 pub mod cpu;
+pub mod debugger;
 pub mod ppu;
 pub mod ram;
 pub mod screen;
 
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use self::cassette::Cassette;
-use self::ppu::Ppu;
+use self::cdl::CodeDataLog;
+use self::ppu::{Ppu, PpuSnapshot};
 use self::ppu::PpuRunResult;
 use self::ram::Ram;
-use self::cpu::{Cpu, Bus as CpuBus};
+use self::cpu::{BusObserver, Cpu, Bus as CpuBus, CpuError, CpuSnapshot, OpenBus, Timing, Tracer, Variant};
+use self::cpu::CpuBus as _;
 use self::screen::Screen;
 
 use sdl2::event::Event;
@@ -16,49 +23,212 @@ use sdl2::event::Event;
 const WIDTH: u32 = 256;
 const HEIGHT: u32 = 240;
 
+/// What `Nes` does when the CPU returns an error.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ErrorPolicy {
+    /// The emulation stops and the error is returned.
+    Stop,
+    /// The error is printed to stderr, and the emulation continues.
+    Log,
+    /// The error is ignored, and the emulation continues.
+    Continue,
+}
+
+/// The state of `Nes` which `restore` brings back.
+/// The cassette isn't included, and the tools attached to `Nes` are kept as they are.
+#[derive(Clone)]
+pub struct Snapshot {
+    cpu: CpuSnapshot,
+    ppu: PpuSnapshot,
+    ram: Ram,
+    open_bus: OpenBus,
+}
+
 pub struct Nes {
     cpu: Cpu,
     ppu: Ppu,
     cassette: Cassette,
     ram: Ram,
+    open_bus: OpenBus,
+    error_policy: ErrorPolicy,
+    observers: Vec<Rc<RefCell<dyn BusObserver>>>,
+    code_data_log: Option<Rc<RefCell<CodeDataLog>>>,
 }
 
 impl Nes {
-    pub fn new(path: &str) -> Nes {
-        let cassette = Cassette::new(path).unwrap();
+    pub fn new(path: &str, timing: Timing) -> Nes {
+        Nes::from_cassette(Cassette::new(path).unwrap(), timing)
+    }
 
+    pub fn from_cassette(cassette: Cassette, timing: Timing) -> Nes {
         let mut nes = Nes {
-            cpu: Cpu::new(),
-            ppu: Ppu::new(cassette.character_rom.clone()),
+            cpu: Cpu::new(Variant::Ricoh2A03, timing),
+            ppu: Ppu::new(cassette.character_rom.clone(), cassette.header.mirroring),
             cassette: cassette,
             ram: Ram::new(vec![0; 0x0800]),
+            open_bus: OpenBus::new(),
+            error_policy: ErrorPolicy::Stop,
+            observers: vec![],
+            code_data_log: None,
         };
 
         {
-            let mut bus = CpuBus::new(&nes.cassette, &mut nes.ppu, &mut nes.ram);
+            let mut bus = CpuBus::new(&nes.cassette, &mut nes.ppu, &mut nes.ram, &mut nes.open_bus);
             nes.cpu.reset(&mut bus);
         }
+        // the PPU runs while the CPU resets, like nestest.log starts at the dot 21.
+        nes.ppu.run(nes.cpu.cycle() * 3);
 
         nes
     }
 
-    pub fn run(&mut self) {
+    pub fn set_error_policy(&mut self, error_policy: ErrorPolicy) {
+        self.error_policy = error_policy;
+    }
+
+    pub fn set_tracer(&mut self, tracer: Option<Box<dyn Tracer>>) {
+        self.cpu.set_tracer(tracer);
+    }
+
+    /// Sets the log which the CPU and the PPU mark the used bytes of the cassette in.
+    pub fn set_code_data_log(&mut self, code_data_log: Option<Rc<RefCell<CodeDataLog>>>) {
+        if let Some(old) = self.code_data_log.take() {
+            self.remove_observer(&(old as Rc<RefCell<dyn BusObserver>>));
+        }
+        if let Some(ref new) = code_data_log {
+            self.add_observer(new.clone());
+        }
+
+        self.ppu.set_code_data_log(code_data_log.clone());
+        self.code_data_log = code_data_log;
+    }
+
+    /// Adds the observer of the accesses which the CPU performs.
+    pub fn add_observer(&mut self, observer: Rc<RefCell<dyn BusObserver>>) {
+        self.observers.push(observer);
+    }
+
+    /// Returns whether the observer was added.
+    pub fn remove_observer(&mut self, observer: &Rc<RefCell<dyn BusObserver>>) -> bool {
+        let len = self.observers.len();
+        self.observers.retain(|o| !Rc::ptr_eq(o, observer));
+        self.observers.len() != len
+    }
+
+    pub fn cassette(&self) -> &Cassette {
+        &self.cassette
+    }
+
+    pub fn cpu(&self) -> &Cpu {
+        &self.cpu
+    }
+
+    pub fn cpu_mut(&mut self) -> &mut Cpu {
+        &mut self.cpu
+    }
+
+    pub fn ppu(&self) -> &Ppu {
+        &self.ppu
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            cpu: self.cpu.snapshot(),
+            ppu: self.ppu.snapshot(),
+            ram: self.ram.clone(),
+            open_bus: self.open_bus.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.cpu.restore(&snapshot.cpu);
+        self.ppu.restore(&snapshot.ppu);
+        self.ram = snapshot.ram.clone();
+        self.open_bus = snapshot.open_bus.clone();
+    }
+
+    /// Reads the CPU address space without side effects.
+    pub fn peek(&mut self, addr: u16) -> u8 {
+        let mut bus = CpuBus::new(&self.cassette, &mut self.ppu, &mut self.ram, &mut self.open_bus);
+        bus.peek(addr)
+    }
+
+    /// Writes to the CPU address space like the CPU does.
+    pub fn poke(&mut self, addr: u16, data: u8) {
+        let mut bus = CpuBus::new(&self.cassette, &mut self.ppu, &mut self.ram, &mut self.open_bus);
+        bus.write(addr, data);
+    }
+
+    /// Runs 1 CPU instruction and the PPU for the same time.
+    /// Returns whether all background lines have been built.
+    pub fn step(&mut self) -> Result<bool, CpuError> {
+        let (result, has_built_background, dma_stall) = {
+            let mut bus = CpuBus::new(&self.cassette, &mut self.ppu, &mut self.ram, &mut self.open_bus);
+            bus.set_observers(&self.observers, self.cpu.cycle());
+            let result = self.cpu.run(&mut bus);
+            (result, bus.has_built_background(), bus.dma_stall())
+        };
+
+        // the PPU runs for the cycle spent until the error too, to keep up with the CPU.
+        let (cycle, error) = match result {
+            Ok(cycle) => (cycle, None),
+            Err((error, cycle)) => (cycle, Some(error)),
+        };
+
+        // the cycle-stepped CPU has already run the PPU through the bus.
+        let mut has_built_background = if self.cpu.timing.is_cycle_stepped() {
+            has_built_background
+        } else {
+            match self.ppu.run(cycle * 3) {
+                PpuRunResult::FinishedBuildAllBackgroundLine => true,
+                _ => false,
+            }
+        };
+
+        // the PPU keeps running while the DMA stalls the CPU.
+        self.cpu.stall(dma_stall);
+        for _ in 0..dma_stall {
+            if let PpuRunResult::FinishedBuildAllBackgroundLine = self.ppu.run(3) {
+                has_built_background = true;
+            }
+        }
+
+        // NMI is taken before the next instruction.
+        self.cpu.set_nmi(self.ppu.nmi());
+
+        if let Some(error) = error {
+            self.handle_error(error)?;
+        }
+
+        if has_built_background {
+            if let Some(profiler) = self.cpu.profiler_mut() {
+                profiler.end_frame();
+            }
+        }
+        Ok(has_built_background)
+    }
+
+    fn handle_error(&self, error: CpuError) -> Result<(), CpuError> {
+        match self.error_policy {
+            ErrorPolicy::Stop => Err(error),
+            ErrorPolicy::Log => {
+                eprintln!("cpu error: {:?}", error);
+                Ok(())
+            },
+            ErrorPolicy::Continue => Ok(()),
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), CpuError> {
         let mut screen = Screen::new(WIDTH, HEIGHT);
 
         'main: loop {
-            let cycle = {
-                let mut bus = CpuBus::new(&self.cassette, &mut self.ppu, &mut self.ram);
-                self.cpu.run(&mut bus)
-            };
-
-            let ppu_run_result = self.ppu.run(cycle * 3);
-            match ppu_run_result {
-                PpuRunResult::FinishedBuildAllBackgroundLine => {
-                    let background = &self.ppu.background;
-                    screen.render_background(&background);
-                },
-                _ => {},
-            };
+            if self.step()? {
+                let background = &self.ppu.background;
+                screen.render_background(&background);
+                screen.render_sprites(&self.ppu.sprites);
+                screen.present();
+            }
 
             for event in screen.events.poll_iter() {
                 match event {
@@ -67,5 +237,88 @@ impl Nes {
                 }
             }
         }
+
+        Ok(())
+    }
+}
+#[cfg(test)]
+mod nes_test {
+    use super::*;
+    use nes::cassette::header::INesHeader;
+
+    struct TracerMock {
+        lines: Rc<RefCell<Vec<String>>>,
     }
-}
\ No newline at end of file
+
+    impl Tracer for TracerMock {
+        fn trace(&mut self, line: &str) {
+            self.lines.borrow_mut().push(String::from(line));
+        }
+    }
+
+    /// Makes `Nes` with `program` at 0x8000, which the reset vector points.
+    pub(crate) fn nes(program: &[u8]) -> Nes {
+        let mut program_rom = vec![0xEA; 0x4000];
+        program_rom[..program.len()].copy_from_slice(program);
+        program_rom[0x3FFC] = 0x00;
+        program_rom[0x3FFD] = 0x80;
+
+        let header = [&b"NES\x1A"[..], &[1, 1], &[0; 10]].concat();
+        let cassette = Cassette {
+            header: INesHeader::new(&header).unwrap(),
+            program_rom: program_rom,
+            character_rom: vec![0; 0x2000],
+        };
+        Nes::from_cassette(cassette, Timing::Instruction)
+    }
+
+    #[test]
+    fn reset_test() {
+        // JMP $C5F5, at 0xC000 which is the mirror of 0x8000
+        let mut nes = nes(&[0x4C, 0xF5, 0xC5]);
+        nes.cpu_mut().registers.PC = 0xC000;
+        let lines = Rc::new(RefCell::new(vec![]));
+        nes.set_tracer(Some(Box::new(TracerMock { lines: lines.clone() })));
+        nes.step().unwrap();
+
+        // the first line of nestest.log
+        assert_eq!(*lines.borrow(), vec![
+            "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7",
+        ]);
+    }
+
+    #[test]
+    fn open_bus_and_program_rom_write_test() {
+        // LDA $6000; STA $8000; NOP
+        let mut nes = nes(&[0xAD, 0x00, 0x60, 0x8D, 0x00, 0x80]);
+        for _ in 0..3 {
+            assert!(nes.step().is_ok());
+        }
+
+        // the upper byte of the operand is left on the bus.
+        assert_eq!(nes.cpu().registers.A, 0x60);
+        assert_eq!(nes.cpu().registers.PC, 0x8007);
+        assert_eq!(nes.peek(0x8000), 0xAD);
+    }
+
+    #[test]
+    fn continue_on_error_test() {
+        // XAA isn't supported; LDA $0300
+        let mut nes = nes(&[0x8B, 0xAD, 0x00, 0x03]);
+        nes.set_error_policy(ErrorPolicy::Continue);
+        let (cycle, dot) = (nes.cpu().cycle(), nes.ppu().cycle);
+
+        // the PPU runs for the fetch of the opecode.
+        assert_eq!(nes.step(), Ok(false));
+        assert_eq!(nes.cpu().cycle(), cycle + 1);
+        assert_eq!(nes.ppu().cycle, dot + 3);
+
+        nes.step().unwrap();
+        assert_eq!(nes.cpu().cycle(), cycle + 5);
+        assert_eq!(nes.ppu().cycle, dot + 15);
+
+        nes.set_error_policy(ErrorPolicy::Stop);
+        nes.cpu_mut().registers.PC = 0x8000;
+        assert_eq!(nes.step(), Err(CpuError::UnknownOpecode { pc: 0x8000, opecode: 0x8B }));
+    }
+}
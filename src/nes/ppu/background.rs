@@ -1,22 +1,25 @@
 use std::slice::Iter;
-use super::tile::Tile;
 
+const WIDTH: usize = 256;
+const HEIGHT: usize = 240;
+
+/// The background of the frame, as the palette numbers of the pixels in every line.
 pub struct Background {
-    tiles: Vec<Tile>,
+    lines: Vec<Vec<u8>>,
 }
 
 impl Background {
     pub fn new() -> Self {
         Background {
-            tiles: Vec::new(),
+            lines: vec![vec![0; WIDTH]; HEIGHT],
         }
     }
 
-    pub fn push(&mut self, tile: Tile) {
-        self.tiles.push(tile);
+    pub fn draw_line(&mut self, y: usize, pixels: Vec<u8>) {
+        self.lines[y] = pixels;
     }
 
-    pub fn iter(&self) -> Iter<Tile> {
-        self.tiles.iter()
+    pub fn iter(&self) -> Iter<'_, Vec<u8>> {
+        self.lines.iter()
     }
-}
\ No newline at end of file
+}
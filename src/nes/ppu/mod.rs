@@ -1,18 +1,24 @@
 pub mod background;
-pub mod sprite;
 pub mod palette;
-pub mod tile;
 pub mod tile_position;
+pub mod object_attribute;
+pub mod sprite_layer;
 
 mod registers;
 mod palette_ram;
 
 use self::registers::Registers;
-use self::palette_ram::PaletteRam;
-use self::tile::Tile;
+use self::palette_ram::{PaletteRam, PaletteType};
 use self::tile_position::TilePosition;
 use self::background::Background;
+use self::object_attribute::ObjectAttribute;
+use self::sprite_layer::{SpriteLayer, SpritePixel};
 
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nes::cassette::header::Mirroring;
+use nes::cdl::{self, CodeDataLog};
 use nes::ram::Ram;
 
 pub struct Ppu {
@@ -20,16 +26,54 @@ pub struct Ppu {
     pub line: usize,
     pub registers: Registers,
     pub background: Background,
+    pub sprites: SpriteLayer,
     pub context: PpuContext,
+    // the background of the current line, as the indexes of the palette ram.
+    background_line: Vec<u8>,
+    // the dot of the current line where the sprite 0 hit is set.
+    sprite_zero_hit_dot: Option<usize>,
+    code_data_log: Option<Rc<RefCell<CodeDataLog>>>,
 }
 
+#[derive(Clone)]
 pub struct PpuContext {
     pub cram: Ram,
     pub vram: Ram,
     pub palette_ram: PaletteRam,
+    // primary OAM: 64 sprites of 4 bytes (y, tile index, attributes, x).
+    pub oam: Ram,
+    pub mirroring: Mirroring,
+}
+
+impl PpuContext {
+    /// Reads `addr` of the nametables from 0x2000 through the mirroring.
+    pub fn read_nametable(&self, addr: u16) -> u8 {
+        self.vram.read(self.mirroring.vram_addr(addr))
+    }
+}
+
+/// The state of `Ppu` which `restore` brings back.
+/// The background is drawn over in every frame, so it isn't kept.
+#[derive(Clone)]
+pub struct PpuSnapshot {
+    cycle: usize,
+    line: usize,
+    registers: Registers,
+    context: PpuContext,
+    background_line: Vec<u8>,
+    sprites: SpriteLayer,
+    sprite_zero_hit_dot: Option<usize>,
 }
 
 const CLOCK_TO_RENDER_LINE: usize = 341;
+const LINES: usize = 262;
+const VISIBLE_LINES: usize = 240;
+const VISIBLE_WIDTH: usize = 256;
+const VBLANK_LINE: usize = 241;
+const PRE_RENDER_LINE: usize = 261;
+const PATTERN_BYTES_LENGTH: usize = 16;
+const NAMETABLE_BYTES_LENGTH: u16 = 0x0400;
+const ATTRIBUTE_TABLE_OFFSET: u16 = 0x03C0;
 
 pub enum PpuRunResult {
     CountUpCycle,
@@ -38,22 +82,60 @@ pub enum PpuRunResult {
 }
 
 impl Ppu {
-    pub fn new(character_rom: Vec<u8>) -> Self {
+    pub fn new(character_rom: Vec<u8>, mirroring: Mirroring) -> Self {
         Ppu {
             cycle: 0,
             line: 0,
             registers: Registers::new(),
             background: Background::new(),
+            sprites: SpriteLayer::new(),
             context: PpuContext {
                 cram: Ram::new(character_rom),
                 vram: Ram::new(vec![0; 0x2000]),
                 palette_ram: PaletteRam::new(),
-            }
+                oam: Ram::new(vec![0; 0x100]),
+                mirroring: mirroring,
+            },
+            background_line: vec![0; VISIBLE_WIDTH],
+            sprite_zero_hit_dot: None,
+            code_data_log: None,
+        }
+    }
+
+    /// Sets the log which the rendered and read patterns of the character rom are marked in.
+    pub fn set_code_data_log(&mut self, code_data_log: Option<Rc<RefCell<CodeDataLog>>>) {
+        self.code_data_log = code_data_log;
+    }
 
+    pub fn snapshot(&self) -> PpuSnapshot {
+        PpuSnapshot {
+            cycle: self.cycle,
+            line: self.line,
+            registers: self.registers.clone(),
+            context: self.context.clone(),
+            background_line: self.background_line.clone(),
+            sprites: self.sprites.clone(),
+            sprite_zero_hit_dot: self.sprite_zero_hit_dot,
         }
     }
 
+    pub fn restore(&mut self, snapshot: &PpuSnapshot) {
+        self.cycle = snapshot.cycle;
+        self.line = snapshot.line;
+        self.registers = snapshot.registers.clone();
+        self.context = snapshot.context.clone();
+        self.background_line = snapshot.background_line.clone();
+        self.sprites = snapshot.sprites.clone();
+        self.sprite_zero_hit_dot = snapshot.sprite_zero_hit_dot;
+    }
+
     pub fn read(&mut self, addr: u16) -> u8 {
+        // PPUDATA reads the pattern table in 0x0000-0x1FFF.
+        let ppu_addr = self.registers.v.read();
+        if addr == 0x0007 && ppu_addr < 0x2000 {
+            self.log_character_rom(ppu_addr as usize, 1, cdl::READ);
+        }
+
         self.registers.read(addr, &mut self.context)
     }
 
@@ -61,56 +143,441 @@ impl Ppu {
         self.registers.write(addr, data, &mut self.context);
     }    
 
+    /// The NMI output, which is connected to the NMI line of the CPU.
+    pub fn nmi(&self) -> bool {
+        self.registers.is_nmi_asserted()
+    }
+
     pub fn run(&mut self, cycle: usize) -> PpuRunResult {
         self.cycle += cycle;
+        self.update_sprite_zero_hit();
 
         if self.cycle < CLOCK_TO_RENDER_LINE {
             return PpuRunResult::CountUpCycle;
         }
 
         self.cycle -= CLOCK_TO_RENDER_LINE;
+        self.finish_line();
         self.line += 1;
+        if self.line == LINES {
+            self.line = 0;
+        }
 
-        // is need building a background line.
-        if self.line <= 240 && self.line % 8 == 0 {
-            /*
-                The name table has such a structure.
-
-                nametable: has 4 nametable. (ID: 0-3)
-                x: 32 tiles(8x8 pixel) in 256 pixel.
-                y: 30 tiles(8x8 pixel) in 240 pixel.
-                +------------+------------+
-                |            |            |
-                |  0(0x2000) |  1(0x2400) |
-                |            |            |
-                +------------+------------+
-                |            |            |
-                |  2(0x2800) |  3(0x2C00) |
-                |            |            |
-                +------------+------------+
-
-                name table: 0, 2 -> start x position is 0.
-                name table: 1, 3 -> start x position is 32.
-            */
-
-            let nametable_id = self.registers.get_nametable_id();
-            let pos_x_start = (nametable_id % 2) * 32;
-            let pos_x_end = pos_x_start + 32;
-            let pos_y = (self.line / 8) as u8;
-
-            for pos_x in pos_x_start..pos_x_end {
-                let tile_pos = TilePosition::new(pos_x, pos_y);
-                let tile = Tile::build(tile_pos, &self.context);
-                self.background.push(tile);
-            }
+        match self.line {
+            VBLANK_LINE => self.registers.ppu_status.vblank = true,
+            PRE_RENDER_LINE => self.registers.ppu_status.clear(),
+            _ => {},
         }
+        self.start_line();
 
         // is not finished building all the background lines.
-        if self.line < 262 {
+        if self.line != 0 {
             return PpuRunResult::FinishedBuildBackgroundLine;
         }
 
-        self.line = 0;
         PpuRunResult::FinishedBuildAllBackgroundLine
     }
-}
\ No newline at end of file
+
+    /// Draws the current line, and moves the scroll position to the next line.
+    fn finish_line(&mut self) {
+        if self.line < VISIBLE_LINES {
+            let pixels = self.background_line.iter().map(|index| self.background_palette_number(*index)).collect();
+            self.background.draw_line(self.line, pixels);
+            self.render_sprite_line(self.line);
+        }
+
+        let mask = &self.registers.ppu_mask;
+        if !(mask.is_background_enabled() || mask.is_sprites_enabled()) {
+            return;
+        }
+
+        // v is moved to the next row at the dot 256, and to the left of the screen at the dot 257.
+        // At the pre-render line, v is moved to the top of the screen at the dots 280-304.
+        let t = self.registers.t;
+        if self.line < VISIBLE_LINES || self.line == PRE_RENDER_LINE {
+            self.registers.v.increment_y();
+            self.registers.v.copy_horizontal(t);
+        }
+        if self.line == PRE_RENDER_LINE {
+            self.registers.v.copy_vertical(t);
+        }
+    }
+
+    /// Draws the sprites of `line` to the sprite layer, which is cleared at the first line.
+    fn render_sprite_line(&mut self, line: usize) {
+        if line == 0 {
+            self.sprites.clear();
+        }
+        if !self.registers.ppu_mask.is_sprites_enabled() {
+            return;
+        }
+
+        let height = self.registers.ppu_ctrl.get_sprite_height();
+        let pattern_table_addr = self.registers.ppu_ctrl.get_sprite_pattern_table_addr();
+        let (objects, _) = object_attribute::evaluate(&self.context.oam, line, height);
+
+        // the opaque pixel of the lowest index wins, even if it is behind the background.
+        let mut pixels: Vec<Option<(&ObjectAttribute, u8)>> = vec![None; VISIBLE_WIDTH];
+        for object in &objects {
+            let row = object.row_in(line, height).unwrap();
+            let addr = object.pattern_addr(row, height, pattern_table_addr);
+            self.log_character_rom((addr & 0xFFF0) as usize, PATTERN_BYTES_LENGTH, cdl::RENDERED);
+
+            for column in 0..8 {
+                let x = object.x as usize + column;
+                if x >= VISIBLE_WIDTH {
+                    break;
+                }
+
+                let bit = if object.flip_horizontally { column } else { 7 - column };
+                let pattern = self.read_pattern(addr, bit);
+                if pattern != 0 && pixels[x].is_none() {
+                    pixels[x] = Some((object, pattern));
+                }
+            }
+        }
+
+        let show_leftmost = self.registers.ppu_mask.is_sprites_leftmost_enabled();
+        for (x, pixel) in pixels.iter().enumerate() {
+            let (object, pattern) = match *pixel {
+                Some(pixel) => pixel,
+                None => continue,
+            };
+            if x < 8 && !show_leftmost {
+                continue;
+            }
+            if object.behind_background && self.background_pattern(x) != 0 {
+                continue;
+            }
+
+            let palettes = self.context.palette_ram.get_palettes(object.palette_id, PaletteType::Sprite);
+            self.sprites.push(SpritePixel {
+                x: x as u8,
+                y: line as u8,
+                palette_number: palettes.get(pattern as usize).get_palette_number() & 0x3F,
+            });
+        }
+    }
+
+    /// Fetches the background of the new line, and evaluates the sprites for the sprite overflow and the sprite 0 hit.
+    fn start_line(&mut self) {
+        self.sprite_zero_hit_dot = None;
+        if self.line >= VISIBLE_LINES {
+            return;
+        }
+
+        self.background_line = self.fetch_background_line();
+        let mask = &self.registers.ppu_mask;
+        if !(mask.is_background_enabled() || mask.is_sprites_enabled()) {
+            return;
+        }
+
+        let height = self.registers.ppu_ctrl.get_sprite_height();
+        let (_, overflow) = object_attribute::evaluate(&self.context.oam, self.line, height);
+        if overflow {
+            self.registers.ppu_status.sprite_overflow = true;
+        }
+
+        self.sprite_zero_hit_dot = self.find_sprite_zero_hit(self.line);
+        self.update_sprite_zero_hit();
+    }
+
+    fn update_sprite_zero_hit(&mut self) {
+        if let Some(dot) = self.sprite_zero_hit_dot {
+            if self.cycle >= dot {
+                self.registers.ppu_status.sprite_zero_hit = true;
+                self.sprite_zero_hit_dot = None;
+            }
+        }
+    }
+
+    /// The dot where an opaque pixel of the sprite 0 overlaps an opaque background pixel on `line`.
+    /// The hit isn't detected at x = 255, nor at x < 8 if either of them is clipped.
+    fn find_sprite_zero_hit(&self, line: usize) -> Option<usize> {
+        let mask = &self.registers.ppu_mask;
+        if !mask.is_sprites_enabled() || self.registers.ppu_status.sprite_zero_hit {
+            return None;
+        }
+
+        let height = self.registers.ppu_ctrl.get_sprite_height();
+        let object = ObjectAttribute::build(0, &self.context.oam);
+        let row = object.row_in(line, height)?;
+        let addr = object.pattern_addr(row, height, self.registers.ppu_ctrl.get_sprite_pattern_table_addr());
+
+        (0..8)
+            .map(|column| (column, object.x as usize + column))
+            .filter(|&(_, x)| x < VISIBLE_WIDTH - 1)
+            .filter(|&(_, x)| x >= 8 || mask.is_sprites_leftmost_enabled())
+            .find(|&(column, x)| {
+                let bit = if object.flip_horizontally { column } else { 7 - column };
+                self.read_pattern(addr, bit) != 0 && self.background_pattern(x) != 0
+            })
+            // the pixel of x is output at the dot of x + 1.
+            .map(|(_, x)| x + 1)
+    }
+
+    /*
+        The background of the line starts at the tile of v, and at the pixel of fine x in it.
+        It continues to the next nametable horizontally.
+
+        +------------+------------+
+        |            |            |
+        |  0(0x2000) |  1(0x2400) |
+        |     +------|-----+      |
+        +-----|------+-----|------+
+        |     +------|-----+      |
+        |  2(0x2800) |  3(0x2C00) |
+        |            |            |
+        +------------+------------+
+    */
+    fn fetch_background_line(&self) -> Vec<u8> {
+        let mask = &self.registers.ppu_mask;
+        if !mask.is_background_enabled() {
+            return vec![0; VISIBLE_WIDTH];
+        }
+
+        let mut addr = self.registers.v;
+        let pattern_table_addr = self.registers.ppu_ctrl.get_background_pattern_table_addr();
+        let mut pixels = Vec::with_capacity(VISIBLE_WIDTH + 8);
+
+        // 33 tiles cover the line shifted by fine x.
+        for _ in 0..33 {
+            let nametable_addr = addr.nametable_id() as u16 * NAMETABLE_BYTES_LENGTH;
+            let tile_pos = TilePosition::new(addr.coarse_x(), addr.coarse_y());
+            let tile_index = self.context.read_nametable(nametable_addr + tile_pos.get_tile_number());
+            let attribute_addr = nametable_addr + ATTRIBUTE_TABLE_OFFSET + tile_pos.get_attribute_id() as u16;
+            let palette_id = tile_pos.get_palette_id(self.context.read_nametable(attribute_addr));
+
+            let tile_addr = pattern_table_addr + tile_index as u16 * PATTERN_BYTES_LENGTH as u16;
+            self.log_character_rom(tile_addr as usize, PATTERN_BYTES_LENGTH, cdl::RENDERED);
+            for bit in (0..8).rev() {
+                let pattern = self.read_pattern(tile_addr + addr.fine_y() as u16, bit);
+                pixels.push(palette_id << 2 | pattern);
+            }
+
+            addr.increment_x();
+        }
+
+        let fine_x = self.registers.fine_x as usize;
+        let mut pixels = pixels[fine_x..fine_x + VISIBLE_WIDTH].to_vec();
+        if !mask.is_background_leftmost_enabled() {
+            for pixel in &mut pixels[..8] {
+                *pixel = 0;
+            }
+        }
+        pixels
+    }
+
+    /// The pattern of the background pixel at `x` of the current line, 0 is transparent.
+    fn background_pattern(&self, x: usize) -> u8 {
+        self.background_line[x] & 0x03
+    }
+
+    /// The transparent pixels show the backdrop color at 0x3F00.
+    fn background_palette_number(&self, index: u8) -> u8 {
+        let pattern = index & 0x03;
+        if pattern == 0 {
+            return self.context.palette_ram.read(0x00) & 0x3F;
+        }
+
+        let palettes = self.context.palette_ram.get_palettes(index >> 2, PaletteType::Background);
+        palettes.get(pattern as usize).get_palette_number() & 0x3F
+    }
+
+    /// Combines `bit` of the 2 planes of the pattern row at `addr`.
+    fn read_pattern(&self, addr: u16, bit: usize) -> u8 {
+        let low = self.context.cram.read(addr);
+        let high = self.context.cram.read(addr + 8);
+        ((low >> bit) & 0x01) | ((high >> bit) & 0x01) << 1
+    }
+
+    fn log_character_rom(&self, offset: usize, len: usize, flag: u8) {
+        if let Some(ref code_data_log) = self.code_data_log {
+            code_data_log.borrow_mut().log_chr(offset, len, flag);
+        }
+    }
+}
+
+#[cfg(test)]
+mod ppu_test {
+    use super::*;
+
+    #[test]
+    fn vblank_test() {
+        let mut ppu = Ppu::new(vec![0; 0x2000], Mirroring::Horizontal);
+        ppu.write(0x0000, 0x80);
+
+        for _ in 0..240 {
+            ppu.run(CLOCK_TO_RENDER_LINE);
+        }
+        assert!(!ppu.nmi());
+
+        ppu.run(CLOCK_TO_RENDER_LINE);
+        assert_eq!(ppu.line, VBLANK_LINE);
+        assert!(ppu.nmi());
+
+        // reading PPUSTATUS releases the line.
+        assert_eq!(ppu.read(0x0002) & 0x80, 0x80);
+        assert!(!ppu.nmi());
+
+        ppu.registers.ppu_status.vblank = true;
+        for _ in VBLANK_LINE..PRE_RENDER_LINE {
+            ppu.run(CLOCK_TO_RENDER_LINE);
+        }
+        assert!(!ppu.nmi());
+    }
+
+    fn write_sprite(ppu: &mut Ppu, index: u16, bytes: [u8; 4]) {
+        for (i, byte) in bytes.iter().enumerate() {
+            ppu.context.oam.write(index * 4 + i as u16, *byte);
+        }
+    }
+
+    #[test]
+    fn render_sprite_line_test() {
+        let mut character_rom = vec![0; 0x2000];
+        // tile 1: only the left pixel of the top row is opaque (pattern 1).
+        character_rom[0x0010] = 0b10000000;
+        // tile 2: all opaque (pattern 3).
+        for row in 0..16 {
+            character_rom[0x0020 + row] = 0xFF;
+        }
+        let mut ppu = Ppu::new(character_rom, Mirroring::Horizontal);
+        ppu.context.palette_ram.write(0x15, 0x16);
+        ppu.context.palette_ram.write(0x1B, 0x2A);
+        ppu.write(0x0001, 0b00011110);
+
+        // flipped horizontally, so the opaque pixel is the right end.
+        write_sprite(&mut ppu, 0, [9, 1, 0b01000001, 20]);
+        ppu.render_sprite_line(10);
+        assert_eq!(ppu.sprites.iter().collect::<Vec<_>>(), vec![
+            &SpritePixel { x: 27, y: 10, palette_number: 0x16 },
+        ]);
+
+        // the background tile 2 at (0, 0) hides the sprite 0 behind it, and the sprite 0 hides the sprite 1.
+        ppu.context.vram.write(0x0000, 2);
+        write_sprite(&mut ppu, 0, [0, 1, 0b00100001, 0]);
+        write_sprite(&mut ppu, 1, [0, 2, 0b00000010, 0]);
+        ppu.line = 1;
+        ppu.start_line();
+        ppu.sprites.clear();
+        ppu.render_sprite_line(1);
+        let xs = ppu.sprites.iter().map(|pixel| pixel.x).collect::<Vec<_>>();
+        assert_eq!(xs, vec![1, 2, 3, 4, 5, 6, 7]);
+        assert!(ppu.sprites.iter().all(|pixel| pixel.palette_number == 0x2A));
+    }
+
+    #[test]
+    fn sprite_zero_hit_test() {
+        let mut character_rom = vec![0; 0x2000];
+        // tile 1: all opaque.
+        for row in 0..16 {
+            character_rom[0x0010 + row] = 0xFF;
+        }
+        let mut ppu = Ppu::new(character_rom, Mirroring::Horizontal);
+        // the background tile 1 at (31, 0), with the sprite 0 at x = 250.
+        ppu.context.vram.write(31, 1);
+        write_sprite(&mut ppu, 0, [0, 1, 0, 250]);
+        ppu.write(0x0001, 0b00011110);
+
+        ppu.run(CLOCK_TO_RENDER_LINE);
+        assert_eq!(ppu.line, 1);
+        ppu.run(250);
+        assert_eq!(ppu.registers.ppu_status.sprite_zero_hit, false);
+        ppu.run(1);
+        assert_eq!(ppu.registers.ppu_status.sprite_zero_hit, true);
+
+        // cleared at the pre-render line.
+        for _ in 1..PRE_RENDER_LINE {
+            ppu.run(CLOCK_TO_RENDER_LINE);
+        }
+        assert_eq!(ppu.registers.ppu_status.sprite_zero_hit, false);
+    }
+
+    #[test]
+    fn sprite_zero_hit_exceptions_test() {
+        let mut character_rom = vec![0; 0x2000];
+        for row in 0..16 {
+            character_rom[0x0010 + row] = 0xFF;
+        }
+        // tile 2: only the left pixel is opaque.
+        character_rom[0x0020] = 0b10000000;
+        let mut ppu = Ppu::new(character_rom, Mirroring::Horizontal);
+        for x in 0..32 {
+            ppu.context.vram.write(x, 1);
+        }
+        ppu.line = 1;
+        let hit_dot = |ppu: &mut Ppu, mask: u8, sprite: [u8; 4]| {
+            ppu.write(0x0001, mask);
+            write_sprite(ppu, 0, sprite);
+            ppu.start_line();
+            ppu.sprite_zero_hit_dot
+        };
+
+        // x = 255 isn't hit.
+        assert_eq!(hit_dot(&mut ppu, 0b00011110, [0, 2, 0, 255]), None);
+
+        // x < 8 isn't hit, if the background or the sprites are clipped.
+        assert_eq!(hit_dot(&mut ppu, 0b00011110, [0, 2, 0, 7]), Some(8));
+        assert_eq!(hit_dot(&mut ppu, 0b00011010, [0, 2, 0, 7]), None);
+        assert_eq!(hit_dot(&mut ppu, 0b00011100, [0, 2, 0, 7]), None);
+        assert_eq!(hit_dot(&mut ppu, 0b00011100, [0, 2, 0, 8]), Some(9));
+    }
+
+    #[test]
+    fn sprite_overflow_test() {
+        let mut ppu = Ppu::new(vec![0; 0x2000], Mirroring::Horizontal);
+        for index in 0..9 {
+            write_sprite(&mut ppu, index, [0, 0, 0, 0]);
+        }
+        ppu.run(CLOCK_TO_RENDER_LINE);
+        assert_eq!(ppu.registers.ppu_status.sprite_overflow, false);
+
+        // the evaluation runs only while rendering.
+        ppu.write(0x0001, 0b00001000);
+        ppu.run(CLOCK_TO_RENDER_LINE);
+        assert_eq!(ppu.registers.ppu_status.sprite_overflow, true);
+    }
+
+    #[test]
+    fn scroll_test() {
+        let mut character_rom = vec![0; 0x2000];
+        // tile 1: the pattern 1 in the row 0, and the pattern 2 in the other rows.
+        character_rom[0x0010] = 0xFF;
+        for row in 1..8 {
+            character_rom[0x0018 + row] = 0xFF;
+        }
+        let mut ppu = Ppu::new(character_rom, Mirroring::Vertical);
+        ppu.context.palette_ram.write(0x00, 0x0F);
+        ppu.context.palette_ram.write(0x01, 0x11);
+        ppu.context.palette_ram.write(0x02, 0x12);
+        // the left tile of the nametable 1 at the row 2, through the nametable 3 which mirrors it.
+        ppu.write(0x0006, 0x2C);
+        ppu.write(0x0006, 0x40);
+        ppu.write(0x0007, 1);
+        assert_eq!(ppu.context.vram.read(0x0400 + 64), 1);
+
+        // scroll to x = 252 and y = 17 in the nametable 0 at the pre-render line.
+        ppu.write(0x0000, 0x00);
+        ppu.write(0x0001, 0b00001010);
+        ppu.read(0x0002);
+        ppu.write(0x0005, 252);
+        ppu.write(0x0005, 17);
+        ppu.line = PRE_RENDER_LINE;
+        ppu.run(CLOCK_TO_RENDER_LINE);
+        assert_eq!(ppu.line, 0);
+
+        // the tile starts at the pixel 4 of the line 0, its row 1 is in the line 0.
+        ppu.run(CLOCK_TO_RENDER_LINE);
+        let line_0 = &ppu.background.iter().next().unwrap()[..16];
+        assert_eq!(line_0, &[0x0F, 0x0F, 0x0F, 0x0F, 0x12, 0x12, 0x12, 0x12, 0x12, 0x12, 0x12, 0x12, 0x0F, 0x0F, 0x0F, 0x0F]);
+
+        // the horizontal scroll is changed in the middle of the frame, it is applied from the next line.
+        ppu.write(0x0005, 0);
+        ppu.run(CLOCK_TO_RENDER_LINE);
+        ppu.run(CLOCK_TO_RENDER_LINE);
+        let lines = ppu.background.iter().skip(1).take(2).map(|line| line[..12].to_vec()).collect::<Vec<_>>();
+        assert_eq!(lines[0][4..12], [0x12; 8]);
+        assert_eq!(lines[1][..8], [0x0F; 8]);
+    }
+}
@@ -0,0 +1,170 @@
+use nes::ram::Ram;
+
+const OBJECT_BYTES_LENGTH: usize = 4;
+const OBJECTS_LENGTH: usize = 64;
+const MAX_OBJECTS_IN_LINE: usize = 8;
+
+/// A sprite in OAM.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ObjectAttribute {
+    // the index in OAM, the lower index has the higher priority.
+    pub index: u8,
+    // the sprite is drawn from the next line of y.
+    pub y: u8,
+    pub tile_index: u8,
+    pub palette_id: u8,
+    pub behind_background: bool,
+    pub flip_horizontally: bool,
+    pub flip_vertically: bool,
+    pub x: u8,
+}
+
+impl ObjectAttribute {
+    pub fn build(index: u8, oam: &Ram) -> Self {
+        let start_idx = index as usize * OBJECT_BYTES_LENGTH;
+        let bytes = oam.read_range(start_idx..start_idx + OBJECT_BYTES_LENGTH);
+        let attribute = bytes[2];
+
+        ObjectAttribute {
+            index: index,
+            y: bytes[0],
+            tile_index: bytes[1],
+            palette_id:         attribute & 0b00000011,
+            behind_background: (attribute & 0b00100000) >> 5 == 1,
+            flip_horizontally: (attribute & 0b01000000) >> 6 == 1,
+            flip_vertically:   (attribute & 0b10000000) >> 7 == 1,
+            x: bytes[3],
+        }
+    }
+
+    /// The row of the sprite drawn on `line`, if the sprite is on the line.
+    pub fn row_in(&self, line: usize, height: usize) -> Option<usize> {
+        row_of(self.y, line, height)
+    }
+
+    /// The address of the pattern of `row`, which is flipped already.
+    pub fn pattern_addr(&self, row: usize, height: usize, pattern_table_addr: u16) -> u16 {
+        let row = if self.flip_vertically { height - 1 - row } else { row };
+
+        // 8x16 sprites select the pattern table by bit 0, and the bottom half is the next tile.
+        let (table_addr, tile_index) = if height == 16 {
+            let table_addr = (self.tile_index as u16 & 0x01) * 0x1000;
+            (table_addr, (self.tile_index & 0xFE) as u16 + (row / 8) as u16)
+        } else {
+            (pattern_table_addr, self.tile_index as u16)
+        };
+
+        table_addr + tile_index * 16 + (row % 8) as u16
+    }
+}
+
+fn row_of(y: u8, line: usize, height: usize) -> Option<usize> {
+    let top = y as usize + 1;
+    if line >= top && line < top + height {
+        Some(line - top)
+    } else {
+        None
+    }
+}
+
+/// Finds the sprites on `line` in OAM, up to 8 sprites, and whether the sprite overflow is detected.
+///
+/// After 8 sprites are found, the hardware increments the byte offset in the sprite as well as the index,
+/// so it takes the tile index, the attributes or x as y. The overflow is both missed and falsely detected.
+pub fn evaluate(oam: &Ram, line: usize, height: usize) -> (Vec<ObjectAttribute>, bool) {
+    let mut objects = vec![];
+    let mut index = 0;
+    while index < OBJECTS_LENGTH && objects.len() < MAX_OBJECTS_IN_LINE {
+        let object = ObjectAttribute::build(index as u8, oam);
+        if object.row_in(line, height).is_some() {
+            objects.push(object);
+        }
+        index += 1;
+    }
+
+    let mut offset = 0;
+    while index < OBJECTS_LENGTH {
+        let y = oam.read((index * OBJECT_BYTES_LENGTH + offset) as u16);
+        if row_of(y, line, height).is_some() {
+            return (objects, true);
+        }
+        index += 1;
+        offset = (offset + 1) % OBJECT_BYTES_LENGTH;
+    }
+
+    (objects, false)
+}
+
+#[cfg(test)]
+mod object_attribute_test {
+    use super::*;
+
+    #[test]
+    fn build_test() {
+        let mut oam = Ram::new(vec![0; 0x100]);
+        oam.write(0x04, 0x10);
+        oam.write(0x05, 0x20);
+        oam.write(0x06, 0b11100011);
+        oam.write(0x07, 0x30);
+
+        assert_eq!(ObjectAttribute::build(1, &oam), ObjectAttribute {
+            index: 1,
+            y: 0x10,
+            tile_index: 0x20,
+            palette_id: 3,
+            behind_background: true,
+            flip_horizontally: true,
+            flip_vertically: true,
+            x: 0x30,
+        });
+    }
+
+    #[test]
+    fn pattern_addr_test() {
+        let mut oam = Ram::new(vec![0; 0x100]);
+        oam.write(0x01, 0x21);
+        let mut object = ObjectAttribute::build(0, &oam);
+
+        assert_eq!(object.row_in(0, 8), None);
+        assert_eq!(object.row_in(1, 8), Some(0));
+        assert_eq!(object.row_in(9, 8), None);
+        assert_eq!(object.pattern_addr(2, 8, 0x1000), 0x1212);
+
+        // 8x16: the bottom half of the table 1.
+        assert_eq!(object.pattern_addr(10, 16, 0x0000), 0x1212);
+
+        object.flip_vertically = true;
+        assert_eq!(object.pattern_addr(2, 8, 0x0000), 0x0215);
+        assert_eq!(object.pattern_addr(2, 16, 0x0000), 0x1215);
+    }
+
+    #[test]
+    fn evaluate_test() {
+        let mut oam = Ram::new(vec![0xFF; 0x100]);
+        for index in 0..10 {
+            oam.write(index * 4, 0x10);
+        }
+
+        let (objects, overflow) = evaluate(&oam, 0x11, 8);
+        assert_eq!(objects.len(), 8);
+        assert_eq!(objects.iter().map(|object| object.index).collect::<Vec<_>>(), (0..8).collect::<Vec<_>>());
+        assert!(overflow);
+        assert_eq!(evaluate(&oam, 0x10, 8), (vec![], false));
+    }
+
+    #[test]
+    fn overflow_bug_test() {
+        let mut oam = Ram::new(vec![0xFF; 0x100]);
+        for index in 0..8 {
+            oam.write(index * 4, 0x10);
+        }
+
+        // y of the sprite 9 is missed, because the tile index of it is taken as y.
+        oam.write(9 * 4, 0x10);
+        assert_eq!(evaluate(&oam, 0x11, 8).1, false);
+
+        // x of the sprite 11 is taken as y.
+        oam.write(11 * 4 + 3, 0x10);
+        assert_eq!(evaluate(&oam, 0x11, 8).1, true);
+    }
+}
@@ -7,6 +7,7 @@ pub enum PaletteType {
     Background,
 }
 
+#[derive(Clone)]
 pub struct PaletteRam(Ram);
 impl PaletteRam {
     pub fn new() -> Self {
@@ -1,28 +1,36 @@
 mod ppu_ctrl;
 mod ppu_mask;
-mod ppu_scroll;
-mod ppu_addr;
+mod ppu_status;
+mod oam_addr;
 mod ppu_data;
+mod vram_addr;
 
 use self::ppu_ctrl::PpuCtrl;
 use self::ppu_mask::PpuMask;
-use self::ppu_scroll::PpuScroll;
-use self::ppu_addr::PpuAddr;
+use self::ppu_status::PpuStatus;
+use self::oam_addr::OamAddr;
 use self::ppu_data::PpuData;
+use self::vram_addr::VramAddr;
 
-use nes::ram::Ram;
 use nes::ppu::PpuContext;
 
+#[derive(Clone)]
 pub struct Registers {
     pub ppu_ctrl: PpuCtrl,
     pub ppu_mask: PpuMask,
-    pub ppu_addr: PpuAddr,
+    pub ppu_status: PpuStatus,
+    pub oam_addr: OamAddr,
     pub ppu_data: PpuData,
-    pub ppu_scroll: PpuScroll,
-
-    // unimplemented!
-    // pub ppu_status:,
-    // pub oam:,
+    // the current VRAM address (v), which is also the scroll position while rendering.
+    pub v: VramAddr,
+    // the temporary VRAM address (t), which is the scroll position of the top left of the screen.
+    pub t: VramAddr,
+    // the fine x scroll (x), which is the pixel column in the tile.
+    pub fine_x: u8,
+    // the write toggle (w) shared by PPUSCROLL and PPUADDR. false: first write; true: second write
+    write_latch: bool,
+    // the last data written to the registers, which fills the undriven bits of PPUSTATUS.
+    io_latch: u8,
 }
 
 impl Registers {
@@ -30,32 +38,98 @@ impl Registers {
         Registers {
             ppu_ctrl: PpuCtrl::new(),
             ppu_mask: PpuMask::new(),
-            ppu_scroll: PpuScroll::new(),
-            ppu_addr: PpuAddr::new(),
+            ppu_status: PpuStatus::new(),
+            oam_addr: OamAddr::new(),
             ppu_data: PpuData::new(),
+            v: VramAddr::new(),
+            t: VramAddr::new(),
+            fine_x: 0,
+            write_latch: false,
+            io_latch: 0,
         }
     }
 
+    /// `addr` is mirrored every 8 bytes.
     pub fn write(&mut self, addr: u16, data: u8, ppu_context: &mut PpuContext) {
-        match addr {
-            0x0000 => self.ppu_ctrl.write(data),
+        self.io_latch = data;
+        match addr & 0x0007 {
+            0x0000 => self.ppu_ctrl_write(data),
             0x0001 => self.ppu_mask.write(data),
-            0x0005 => self.ppu_scroll.write(data),
-            0x0006 => self.ppu_addr.write(data as u16),
-            0x0007 => self.ppu_data_write(data, ppu_context),
-            _ => unimplemented!()
+            // PPUSTATUS is read only
+            0x0002 => {},
+            0x0003 => self.oam_addr.write(data),
+            0x0004 => self.oam_data_write(data, ppu_context),
+            0x0005 => self.ppu_scroll_write(data),
+            0x0006 => self.ppu_addr_write(data),
+            // 0x0007
+            _ => self.ppu_data_write(data, ppu_context),
         }
     }
 
     pub fn read(&mut self, addr: u16, ppu_context: &mut PpuContext) -> u8 {
-        match addr {
+        match addr & 0x0007 {
+            0x0002 => self.ppu_status_read(),
+            0x0004 => self.oam_data_read(ppu_context),
             0x0007 => self.ppu_data_read(ppu_context),
-            _ => unimplemented!(),
+            // write only registers return the last written data
+            _ => self.io_latch,
+        }
+    }
+
+    /// Whether the PPU asserts the NMI line of the CPU.
+    pub fn is_nmi_asserted(&self) -> bool {
+        self.ppu_status.vblank && self.ppu_ctrl.is_nmi_enabled()
+    }
+
+    fn ppu_status_read(&mut self) -> u8 {
+        self.write_latch = false;
+        self.ppu_status.read(self.io_latch)
+    }
+
+    fn oam_data_read(&self, ppu_context: &PpuContext) -> u8 {
+        let addr = self.oam_addr.read();
+        let data = ppu_context.oam.read(addr as u16);
+        // bits 2-4 of the sprite attributes don't exist.
+        if addr % 4 == 2 {
+            data & 0b11100011
+        } else {
+            data
+        }
+    }
+
+    fn oam_data_write(&mut self, data: u8, ppu_context: &mut PpuContext) {
+        ppu_context.oam.write(self.oam_addr.read() as u16, data);
+        self.oam_addr.increment();
+    }
+
+    fn ppu_ctrl_write(&mut self, data: u8) {
+        self.ppu_ctrl.write(data);
+        self.t.set_nametable_id(self.ppu_ctrl.get_nametable_id());
+    }
+
+    fn ppu_scroll_write(&mut self, data: u8) {
+        if self.write_latch {
+            self.t.set_fine_y(data & 0x07);
+            self.t.set_coarse_y(data >> 3);
+        } else {
+            self.fine_x = data & 0x07;
+            self.t.set_coarse_x(data >> 3);
+        }
+        self.write_latch = !self.write_latch;
+    }
+
+    fn ppu_addr_write(&mut self, data: u8) {
+        if self.write_latch {
+            self.t.set_lower(data);
+            self.v = self.t;
+        } else {
+            self.t.set_upper(data);
         }
+        self.write_latch = !self.write_latch;
     }
 
     fn ppu_data_read(&mut self, ppu_context: &mut PpuContext) -> u8 {
-        let addr = self.ppu_addr.read();
+        let addr = self.v.read();
         let data = self.ppu_data.read(addr, ppu_context);
         self.increment_vram();
         
@@ -63,31 +137,31 @@ impl Registers {
     }
 
     fn ppu_data_write(&mut self, data: u8, ppu_context: &mut PpuContext) {
-        let addr = self.ppu_addr.read();
+        let addr = self.v.read();
         self.ppu_data.write(addr, data, ppu_context);
         self.increment_vram();
     }
 
-    pub fn get_nametable_id(&self) -> u8 {
-        self.ppu_ctrl.get_nametable_id()
-    }
-
     fn increment_vram(&mut self) {
         let offset = self.ppu_ctrl.get_vram_increment_offset();
-        self.ppu_addr.update(offset);
+        self.v.increment(offset);
     }
 }
 
 #[cfg(test)]
 mod registers_test {
     use super::*;
+    use nes::cassette::header::Mirroring;
     use nes::ppu::palette_ram::PaletteRam;
+    use nes::ram::Ram;
 
     fn dummy_ppu_context() -> PpuContext {
         PpuContext {
             vram: Ram::new(vec![0;0x20]),
             cram: Ram::new(vec![0;0x20]),
             palette_ram: PaletteRam::new(),
+            oam: Ram::new(vec![0;0x100]),
+            mirroring: Mirroring::Horizontal,
         }
     }
 
@@ -95,12 +169,12 @@ mod registers_test {
     fn increment_vram_test() {
         let mut registers = Registers::new();
         registers.increment_vram();
-        assert_eq!(registers.ppu_addr.read(), 0x0000 + 1); // increment 1
+        assert_eq!(registers.v.read(), 0x0000 + 1); // increment 1
 
         let mut registers = Registers::new();
         registers.ppu_ctrl.write(0b00000100);
         registers.increment_vram();
-        assert_eq!(registers.ppu_addr.read(), 0x0000 + 32); // increment 32
+        assert_eq!(registers.v.read(), 0x0000 + 32); // increment 32
     }
 
     #[test]
@@ -109,6 +183,7 @@ mod registers_test {
         let mut registers = Registers::new();
         registers.write(0x0000, 0xFF, &mut ppu_context);
         assert_eq!(registers.ppu_ctrl.read(), 0xFF);
+        assert_eq!(registers.t.nametable_id(), 3);
     }
 
     #[test]
@@ -119,6 +194,14 @@ mod registers_test {
         assert_eq!(registers.ppu_mask.read(), 0xFF);
     }
 
+    #[test]
+    fn write_mirrored_test() {
+        let mut ppu_context = dummy_ppu_context();
+        let mut registers = Registers::new();
+        registers.write(0x0009, 0xFF, &mut ppu_context);
+        assert_eq!(registers.ppu_mask.read(), 0xFF);
+    }
+
     #[test]
     fn write_ppu_scroll_test() {
         let mut ppu_context = dummy_ppu_context();
@@ -126,8 +209,10 @@ mod registers_test {
 
         registers.write(0x0005, 0xFF, &mut ppu_context);
         registers.write(0x0005, 0xEE, &mut ppu_context);
-        assert_eq!(registers.ppu_scroll.x, 0xFF);
-        assert_eq!(registers.ppu_scroll.y, 0xEE);
+        assert_eq!((registers.t.coarse_x(), registers.fine_x), (0x1F, 0x07));
+        assert_eq!((registers.t.coarse_y(), registers.t.fine_y()), (0x1D, 0x06));
+        // v isn't changed until rendering.
+        assert_eq!(registers.v.read(), 0x0000);
     }
 
     #[test]
@@ -136,7 +221,70 @@ mod registers_test {
         let mut registers = Registers::new();
 
         registers.write(0x0006, 0xFF, &mut ppu_context);
-        assert_eq!(registers.ppu_addr.read(), 0xFF00);
+        assert_eq!(registers.v.read(), 0x0000);
+        registers.write(0x0006, 0x12, &mut ppu_context);
+        // bit 14 is cleared.
+        assert_eq!(registers.v.read(), 0x3F12);
+        assert_eq!(registers.t, registers.v);
+    }
+
+    #[test]
+    fn write_latch_test() {
+        let mut ppu_context = dummy_ppu_context();
+        let mut registers = Registers::new();
+
+        // PPUSCROLL and PPUADDR share the latch.
+        registers.write(0x0005, 0x12, &mut ppu_context);
+        registers.write(0x0006, 0x34, &mut ppu_context);
+        assert_eq!(registers.fine_x, 0x02);
+        assert_eq!(registers.v.read(), 0x0034);
+
+        // reading PPUSTATUS resets the latch.
+        registers.write(0x0006, 0x21, &mut ppu_context);
+        registers.read(0x0002, &mut ppu_context);
+        registers.write(0x0006, 0x23, &mut ppu_context);
+        registers.write(0x0006, 0x45, &mut ppu_context);
+        assert_eq!(registers.v.read(), 0x2345);
+    }
+
+    #[test]
+    fn read_ppu_status_test() {
+        let mut ppu_context = dummy_ppu_context();
+        let mut registers = Registers::new();
+        registers.ppu_status.vblank = true;
+        registers.write(0x0000, 0x80, &mut ppu_context);
+        assert!(registers.is_nmi_asserted());
+
+        // bits 0-4 are the last written data.
+        registers.write(0x0001, 0x1F, &mut ppu_context);
+        assert_eq!(registers.read(0x0002, &mut ppu_context), 0x9F);
+        assert_eq!(registers.read(0x0002, &mut ppu_context), 0x1F);
+        assert!(!registers.is_nmi_asserted());
+    }
+
+    #[test]
+    fn oam_data_test() {
+        let mut ppu_context = dummy_ppu_context();
+        let mut registers = Registers::new();
+
+        registers.write(0x0003, 0xFE, &mut ppu_context);
+        registers.write(0x0004, 0x10, &mut ppu_context);
+        registers.write(0x0004, 0xFF, &mut ppu_context);
+        registers.write(0x0004, 0x30, &mut ppu_context);
+        assert_eq!(ppu_context.oam.read(0xFE), 0x10);
+        assert_eq!(ppu_context.oam.read(0xFF), 0xFF);
+        // wrapped around.
+        assert_eq!(ppu_context.oam.read(0x00), 0x30);
+
+        // reading doesn't increment the address.
+        registers.write(0x0003, 0xFF, &mut ppu_context);
+        assert_eq!(registers.read(0x0004, &mut ppu_context), 0xFF);
+        assert_eq!(registers.read(0x0004, &mut ppu_context), 0xFF);
+
+        registers.write(0x0003, 0x02, &mut ppu_context);
+        registers.write(0x0004, 0xFF, &mut ppu_context);
+        registers.write(0x0003, 0x02, &mut ppu_context);
+        assert_eq!(registers.read(0x0004, &mut ppu_context), 0xE3);
     }
 
     #[test]
@@ -146,7 +294,7 @@ mod registers_test {
 
         registers.ppu_data.buf = 0x10;
         registers.write(0x0007, 0xFF, &mut ppu_context);
-        assert_eq!(registers.ppu_addr.read(), 0x00 + 1); // incremented
+        assert_eq!(registers.v.read(), 0x00 + 1); // incremented
 
         assert_eq!(registers.ppu_data.read(0x0000, &mut ppu_context), 0x10); // read PpuData buf
         assert_eq!(registers.ppu_data.read(0x0000, &mut ppu_context), 0xFF); // read wrote data
@@ -156,12 +304,12 @@ mod registers_test {
     fn read_ppu_data_test() {
         let mut ppu_context = dummy_ppu_context();
         let mut registers = Registers::new();
-        registers.ppu_addr.update(0x0F);
+        registers.v.increment(0x0F);
         registers.ppu_data.write(0x000F, 0xEE, &mut ppu_context);
 
         assert_eq!(registers.read(0x0007, &mut ppu_context), 0x00); // read PpuData buf
-        assert_eq!(registers.ppu_addr.read(), 0x0F + 1);            // incremented
+        assert_eq!(registers.v.read(), 0x0F + 1);                   // incremented
         assert_eq!(registers.read(0x0007, &mut ppu_context), 0xEE); // read wrote data
-        assert_eq!(registers.ppu_addr.read(), 0x0F + 2);            // incremented
+        assert_eq!(registers.v.read(), 0x0F + 2);                   // incremented
     }
 }
\ No newline at end of file
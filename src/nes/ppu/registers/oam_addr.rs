@@ -0,0 +1,38 @@
+#[derive(Clone)]
+pub struct OamAddr {
+    addr: u8,
+}
+
+impl OamAddr {
+    pub fn new() -> Self {
+        OamAddr {
+            addr: 0,
+        }
+    }
+
+    pub fn read(&self) -> u8 {
+        self.addr
+    }
+
+    pub fn write(&mut self, data: u8) {
+        self.addr = data;
+    }
+
+    /// OAMDATA writes increment the address, it wraps around in 256 bytes.
+    pub fn increment(&mut self) {
+        self.addr = self.addr.wrapping_add(1);
+    }
+}
+
+#[cfg(test)]
+mod oam_addr_test {
+    use super::*;
+
+    #[test]
+    fn increment_test() {
+        let mut oam_addr = OamAddr::new();
+        oam_addr.write(0xFF);
+        oam_addr.increment();
+        assert_eq!(oam_addr.read(), 0x00);
+    }
+}
@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct PpuCtrl {
     // 0 = $2000; 1 = $2400; 2 = $2800; 3 = $2C00
     nametable_address: u8,
@@ -11,8 +12,8 @@ pub struct PpuCtrl {
     sprite_size: bool,
     // 0: read backdrop from EXT pins; 1: output color on EXT pins
     ppu_select: bool,
-    // 0: off; 1: on
-    read_backdrop_from_ext: bool,
+    // generate an NMI at the start of vblank. 0: off; 1: on
+    generate_nmi: bool,
 }
 
 impl PpuCtrl {
@@ -24,7 +25,7 @@ impl PpuCtrl {
             background_pattern_table_address: false,
             sprite_size: false,
             ppu_select: false,
-            read_backdrop_from_ext: false,
+            generate_nmi: false,
         }
     }
 
@@ -35,7 +36,7 @@ impl PpuCtrl {
         self.background_pattern_table_address = (data & 0b00010000) >> 4 == 1;
         self.sprite_size                      = (data & 0b00100000) >> 5 == 1;
         self.ppu_select                       = (data & 0b01000000) >> 6 == 1;
-        self.read_backdrop_from_ext           = (data & 0b10000000) >> 7 == 1;
+        self.generate_nmi                     = (data & 0b10000000) >> 7 == 1;
     }
 
     pub fn read(&self) -> u8 {
@@ -45,13 +46,42 @@ impl PpuCtrl {
         (self.background_pattern_table_address as u8) << 4 |
         (self.sprite_size as u8)                      << 5 |
         (self.ppu_select as u8)                       << 6 |
-        (self.read_backdrop_from_ext as u8)           << 7
+        (self.generate_nmi as u8)                     << 7
     }
 
     pub fn get_nametable_id(&self) -> u8 {
         self.nametable_address
     }
 
+    pub fn get_background_pattern_table_addr(&self) -> u16 {
+        if self.background_pattern_table_address {
+            0x1000
+        } else {
+            0x0000
+        }
+    }
+
+    /// The 8x16 sprites select the pattern table by their tile index instead.
+    pub fn get_sprite_pattern_table_addr(&self) -> u16 {
+        if self.sprite_pattern_table_address_8x8 {
+            0x1000
+        } else {
+            0x0000
+        }
+    }
+
+    pub fn get_sprite_height(&self) -> usize {
+        if self.sprite_size {
+            16
+        } else {
+            8
+        }
+    }
+
+    pub fn is_nmi_enabled(&self) -> bool {
+        self.generate_nmi
+    }
+
     pub fn get_vram_increment_offset(&self) -> u8 {
         if self.vram_address_increment_ppudata {
             32
@@ -76,7 +106,7 @@ mod ppu_ctrl_test {
         assert_eq!(ppu_ctrl.background_pattern_table_address, false);
         assert_eq!(ppu_ctrl.sprite_size, true);
         assert_eq!(ppu_ctrl.ppu_select, false);
-        assert_eq!(ppu_ctrl.read_backdrop_from_ext, true);
+        assert_eq!(ppu_ctrl.generate_nmi, true);
     }
 
     #[test]
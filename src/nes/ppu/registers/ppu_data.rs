@@ -1,4 +1,4 @@
-use nes::ram::Ram;
+use nes::cassette::header::Mirroring;
 use nes::ppu::PpuContext;
 
 enum MapType {
@@ -12,16 +12,17 @@ struct PpuMemoryMapRule;
 impl PpuMemoryMapRule {
     fn address_to_map_type(addr: u16) -> MapType {
         match addr {
-            0x0000..0x1FFF => MapType::PatternTable,
-            0x2000..0x2FFF => MapType::Vram,
-            0x3000..0x3EFF => MapType::VramMirror,
-            0x3F00..0x3FFF => MapType::Palette, 
+            0x0000..=0x1FFF => MapType::PatternTable,
+            0x2000..=0x2FFF => MapType::Vram,
+            0x3000..=0x3EFF => MapType::VramMirror,
+            0x3F00..=0x3FFF => MapType::Palette, 
             _ => panic!("Access to out of PPU memory."),
         }
     }
 }
 
 
+#[derive(Clone)]
 pub struct PpuData {
     pub buf: u8,
 }
@@ -32,7 +33,7 @@ impl PpuData {
     }
 
     pub fn write(&mut self, addr: u16, data: u8, ppu_context: &mut PpuContext) {
-        let calibrated_addr = self.calibrate_address(addr);
+        let calibrated_addr = self.calibrate_address(addr, ppu_context.mirroring);
 
         match PpuMemoryMapRule::address_to_map_type(addr) {
             MapType::PatternTable => ppu_context.cram.write(calibrated_addr, data),
@@ -43,7 +44,7 @@ impl PpuData {
 
     pub fn read(&mut self, addr: u16, ppu_context: &mut PpuContext) -> u8 {
         let buf = self.buf;
-        let calibrated_addr = self.calibrate_address(addr);
+        let calibrated_addr = self.calibrate_address(addr, ppu_context.mirroring);
 
         match PpuMemoryMapRule::address_to_map_type(addr) {
             MapType::PatternTable => self.buf = ppu_context.cram.read(calibrated_addr),
@@ -57,11 +58,11 @@ impl PpuData {
         buf
     }
     
-    fn calibrate_address(&self, addr: u16) -> u16 {
+    fn calibrate_address(&self, addr: u16, mirroring: Mirroring) -> u16 {
         match PpuMemoryMapRule::address_to_map_type(addr) {
             MapType::PatternTable => addr,
-            MapType::Vram => addr - 0x2000,
-            MapType::VramMirror => addr - 0x3000,
+            MapType::Vram => mirroring.vram_addr(addr - 0x2000),
+            MapType::VramMirror => mirroring.vram_addr(addr - 0x3000),
             MapType::Palette => addr - 0x3F00,
         }
     }
@@ -71,6 +72,7 @@ impl PpuData {
 mod ppu_data_test {
     use super::*;
     use nes::ppu::palette_ram::PaletteRam;
+    use nes::ram::Ram;
 
     #[test]
     fn read_pattern_test() {
@@ -78,6 +80,8 @@ mod ppu_data_test {
             cram: Ram::new(vec![0;0x20]),
             vram: Ram::new(vec![0;0x20]),
             palette_ram: PaletteRam::new(),
+            oam: Ram::new(vec![0;0x100]),
+            mirroring: Mirroring::Horizontal,
         };
 
         ppu_context.cram.write(0x00, 0xFF);
@@ -95,6 +99,8 @@ mod ppu_data_test {
             cram: Ram::new(vec![0;0x20]),
             vram: Ram::new(vec![0;0x20]),
             palette_ram: PaletteRam::new(),
+            oam: Ram::new(vec![0;0x100]),
+            mirroring: Mirroring::Horizontal,
         };
 
         ppu_context.vram.write(0x00, 0xFF);
@@ -112,6 +118,8 @@ mod ppu_data_test {
             cram: Ram::new(vec![0;0x20]),
             vram: Ram::new(vec![0;0x20]),
             palette_ram: PaletteRam::new(),
+            oam: Ram::new(vec![0;0x100]),
+            mirroring: Mirroring::Horizontal,
         };
 
         ppu_context.vram.write(0x00, 0xFF);
@@ -129,6 +137,8 @@ mod ppu_data_test {
             cram: Ram::new(vec![0;0x20]),
             vram: Ram::new(vec![0;0x20]),
             palette_ram: PaletteRam::new(),
+            oam: Ram::new(vec![0;0x100]),
+            mirroring: Mirroring::Horizontal,
         };
 
         ppu_context.vram.write(0x00, 0xFF);
@@ -147,6 +157,8 @@ mod ppu_data_test {
             cram: Ram::new(vec![0;0x20]),
             vram: Ram::new(vec![0;0x20]),
             palette_ram: PaletteRam::new(),
+            oam: Ram::new(vec![0;0x100]),
+            mirroring: Mirroring::Horizontal,
         };
 
         let mut ppu_data = PpuData::new();
@@ -161,6 +173,8 @@ mod ppu_data_test {
             cram: Ram::new(vec![0;0x20]),
             vram: Ram::new(vec![0;0x20]),
             palette_ram: PaletteRam::new(),
+            oam: Ram::new(vec![0;0x100]),
+            mirroring: Mirroring::Horizontal,
         };
 
         let mut ppu_data = PpuData::new();
@@ -175,6 +189,8 @@ mod ppu_data_test {
             cram: Ram::new(vec![0;0x20]),
             vram: Ram::new(vec![0;0x20]),
             palette_ram: PaletteRam::new(),
+            oam: Ram::new(vec![0;0x100]),
+            mirroring: Mirroring::Horizontal,
         };
 
         let mut ppu_data = PpuData::new();
@@ -189,6 +205,8 @@ mod ppu_data_test {
             cram: Ram::new(vec![0;0x20]),
             vram: Ram::new(vec![0;0x20]),
             palette_ram: PaletteRam::new(),
+            oam: Ram::new(vec![0;0x100]),
+            mirroring: Mirroring::Horizontal,
         };
 
         let mut ppu_data = PpuData::new();
@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct PpuMask {
   // 0: normal color, 1: produce a greyscale display
   grayscale: bool,
@@ -52,6 +53,22 @@ impl PpuMask {
         (self.emphasize_green as u8)             << 6 |
         (self.emphasize_blue as u8)              << 7
     }
+
+    pub fn is_background_enabled(&self) -> bool {
+        self.show_background
+    }
+
+    pub fn is_background_leftmost_enabled(&self) -> bool {
+        self.show_background_in_leftmost
+    }
+
+    pub fn is_sprites_enabled(&self) -> bool {
+        self.show_sprites
+    }
+
+    pub fn is_sprites_leftmost_enabled(&self) -> bool {
+        self.show_sprites_in_leftmost
+    }
 }
 
 
@@ -0,0 +1,57 @@
+#[derive(Clone)]
+pub struct PpuStatus {
+    // 1: more than 8 sprites on a scanline
+    pub sprite_overflow: bool,
+    // 1: a non-transparent pixel of sprite 0 overlaps a non-transparent background pixel
+    pub sprite_zero_hit: bool,
+    // 0: not in vblank; 1: in vblank
+    pub vblank: bool,
+}
+
+impl PpuStatus {
+    pub fn new() -> Self {
+        PpuStatus {
+            sprite_overflow: false,
+            sprite_zero_hit: false,
+            vblank: false,
+        }
+    }
+
+    /// Bits 0-4 aren't driven by PPUSTATUS, `latch` fills them.
+    /// Reading clears the vblank flag.
+    pub fn read(&mut self, latch: u8) -> u8 {
+        let data = (latch & 0b00011111) |
+            (self.sprite_overflow as u8) << 5 |
+            (self.sprite_zero_hit as u8) << 6 |
+            (self.vblank as u8)          << 7;
+
+        self.vblank = false;
+        data
+    }
+
+    /// Cleared at the pre-render line.
+    pub fn clear(&mut self) {
+        self.sprite_overflow = false;
+        self.sprite_zero_hit = false;
+        self.vblank = false;
+    }
+}
+
+#[cfg(test)]
+mod ppu_status_test {
+    use super::*;
+
+    #[test]
+    fn read_test() {
+        let mut ppu_status = PpuStatus::new();
+        ppu_status.vblank = true;
+        ppu_status.sprite_zero_hit = true;
+
+        assert_eq!(ppu_status.read(0xFF), 0b11011111);
+        // vblank is cleared by the read.
+        assert_eq!(ppu_status.read(0x00), 0b01000000);
+
+        ppu_status.clear();
+        assert_eq!(ppu_status.read(0x00), 0x00);
+    }
+}
@@ -0,0 +1,179 @@
+/*
+    The internal VRAM address of 15 bits, which is both the address of PPUDATA and the scroll position.
+
+    yyy NN YYYYY XXXXX
+    |   |  |     +------ coarse x: the tile column in the nametable
+    |   |  +------------ coarse y: the tile row in the nametable
+    |   +--------------- nametable id
+    +------------------- fine y: the pixel row in the tile
+*/
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct VramAddr(u16);
+
+const COARSE_X: u16    = 0b000_00_00000_11111;
+const COARSE_Y: u16    = 0b000_00_11111_00000;
+const NAMETABLE_X: u16 = 0b000_01_00000_00000;
+const NAMETABLE_Y: u16 = 0b000_10_00000_00000;
+const NAMETABLE: u16   = NAMETABLE_X | NAMETABLE_Y;
+const FINE_Y: u16      = 0b111_00_00000_00000;
+
+// bits changed by the horizontal and the vertical scroll.
+const HORIZONTAL: u16 = COARSE_X | NAMETABLE_X;
+const VERTICAL: u16 = COARSE_Y | NAMETABLE_Y | FINE_Y;
+
+impl VramAddr {
+    pub fn new() -> Self {
+        VramAddr(0)
+    }
+
+    /// The address of PPUDATA.
+    pub fn read(&self) -> u16 {
+        self.0 & 0x3FFF
+    }
+
+    pub fn coarse_x(&self) -> u8 {
+        (self.0 & COARSE_X) as u8
+    }
+
+    pub fn coarse_y(&self) -> u8 {
+        ((self.0 & COARSE_Y) >> 5) as u8
+    }
+
+    pub fn nametable_id(&self) -> u8 {
+        ((self.0 & NAMETABLE) >> 10) as u8
+    }
+
+    pub fn fine_y(&self) -> u8 {
+        ((self.0 & FINE_Y) >> 12) as u8
+    }
+
+    pub fn set_coarse_x(&mut self, coarse_x: u8) {
+        self.0 = (self.0 & !COARSE_X) | (coarse_x as u16 & 0x1F);
+    }
+
+    pub fn set_coarse_y(&mut self, coarse_y: u8) {
+        self.0 = (self.0 & !COARSE_Y) | (coarse_y as u16 & 0x1F) << 5;
+    }
+
+    pub fn set_nametable_id(&mut self, nametable_id: u8) {
+        self.0 = (self.0 & !NAMETABLE) | (nametable_id as u16 & 0x03) << 10;
+    }
+
+    pub fn set_fine_y(&mut self, fine_y: u8) {
+        self.0 = (self.0 & !FINE_Y) | (fine_y as u16 & 0x07) << 12;
+    }
+
+    /// The first write of PPUADDR, bit 14 is cleared.
+    pub fn set_upper(&mut self, data: u8) {
+        self.0 = (self.0 & 0x00FF) | (data as u16 & 0x3F) << 8;
+    }
+
+    /// The second write of PPUADDR.
+    pub fn set_lower(&mut self, data: u8) {
+        self.0 = (self.0 & 0xFF00) | data as u16;
+    }
+
+    /// PPUDATA increments the address by 1 or 32.
+    pub fn increment(&mut self, offset: u8) {
+        self.0 = self.0.wrapping_add(offset as u16) & 0x7FFF;
+    }
+
+    /// Moves to the next tile, it wraps around to the next nametable horizontally.
+    pub fn increment_x(&mut self) {
+        if self.coarse_x() == 31 {
+            self.0 = (self.0 & !COARSE_X) ^ NAMETABLE_X;
+        } else {
+            self.0 += 1;
+        }
+    }
+
+    /// Moves to the next pixel row, it wraps around to the next nametable vertically after 30 rows.
+    /// The rows 30 and 31 are the attribute table, they wrap around in the same nametable.
+    pub fn increment_y(&mut self) {
+        if self.fine_y() < 7 {
+            self.0 += 1 << 12;
+            return;
+        }
+
+        self.set_fine_y(0);
+        match self.coarse_y() {
+            29 => {
+                self.set_coarse_y(0);
+                self.0 ^= NAMETABLE_Y;
+            },
+            31 => self.set_coarse_y(0),
+            coarse_y => self.set_coarse_y(coarse_y + 1),
+        }
+    }
+
+    pub fn copy_horizontal(&mut self, other: VramAddr) {
+        self.0 = (self.0 & !HORIZONTAL) | (other.0 & HORIZONTAL);
+    }
+
+    pub fn copy_vertical(&mut self, other: VramAddr) {
+        self.0 = (self.0 & !VERTICAL) | (other.0 & VERTICAL);
+    }
+}
+
+#[cfg(test)]
+mod vram_addr_test {
+    use super::*;
+
+    #[test]
+    fn set_test() {
+        let mut addr = VramAddr::new();
+        addr.set_coarse_x(0x1F);
+        addr.set_coarse_y(0x1D);
+        addr.set_nametable_id(2);
+        addr.set_fine_y(5);
+        assert_eq!(addr, VramAddr(0b101_10_11101_11111));
+        assert_eq!(addr.read(), 0x1BBF);
+
+        addr.set_upper(0xFF);
+        addr.set_lower(0x12);
+        assert_eq!(addr, VramAddr(0x3F12));
+    }
+
+    #[test]
+    fn increment_x_test() {
+        let mut addr = VramAddr::new();
+        addr.set_coarse_x(30);
+        addr.increment_x();
+        assert_eq!((addr.coarse_x(), addr.nametable_id()), (31, 0));
+        addr.increment_x();
+        assert_eq!((addr.coarse_x(), addr.nametable_id()), (0, 1));
+        addr.set_coarse_x(31);
+        addr.increment_x();
+        assert_eq!((addr.coarse_x(), addr.nametable_id()), (0, 0));
+    }
+
+    #[test]
+    fn increment_y_test() {
+        let mut addr = VramAddr::new();
+        addr.set_fine_y(6);
+        addr.increment_y();
+        assert_eq!((addr.fine_y(), addr.coarse_y()), (7, 0));
+        addr.increment_y();
+        assert_eq!((addr.fine_y(), addr.coarse_y()), (0, 1));
+
+        addr.set_fine_y(7);
+        addr.set_coarse_y(29);
+        addr.increment_y();
+        assert_eq!((addr.coarse_y(), addr.nametable_id()), (0, 2));
+
+        addr.set_fine_y(7);
+        addr.set_coarse_y(31);
+        addr.increment_y();
+        assert_eq!((addr.coarse_y(), addr.nametable_id()), (0, 2));
+    }
+
+    #[test]
+    fn copy_test() {
+        let mut v = VramAddr(0x7FFF);
+        let t = VramAddr(0x0000);
+        v.copy_horizontal(t);
+        assert_eq!(v, VramAddr(0x7BE0));
+        v.copy_vertical(t);
+        assert_eq!(v, VramAddr(0x0000));
+    }
+}
@@ -0,0 +1,35 @@
+use std::slice::Iter;
+
+/// A pixel of the sprites which is drawn over the background.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpritePixel {
+    pub x: u8,
+    pub y: u8,
+    pub palette_number: u8,
+}
+
+/// The sprite pixels of the current frame, the transparent pixels and the pixels behind the background are left out.
+#[derive(Clone)]
+pub struct SpriteLayer {
+    pixels: Vec<SpritePixel>,
+}
+
+impl SpriteLayer {
+    pub fn new() -> Self {
+        SpriteLayer {
+            pixels: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, pixel: SpritePixel) {
+        self.pixels.push(pixel);
+    }
+
+    pub fn clear(&mut self) {
+        self.pixels.clear();
+    }
+
+    pub fn iter(&self) -> Iter<'_, SpritePixel> {
+        self.pixels.iter()
+    }
+}
@@ -1,5 +1,6 @@
 use std::ops::Range;
 
+#[derive(Clone)]
 pub struct Ram {
     pub buf: Vec<u8>,
 }
@@ -6,6 +6,7 @@ use sdl2::render::WindowCanvas;
 use sdl2::rect::Point;
 
 use nes::ppu::background::Background;
+use nes::ppu::sprite_layer::SpriteLayer;
 
 pub struct Screen {
     pub canvas: WindowCanvas,
@@ -42,27 +43,25 @@ impl Screen {
     }
 
     pub fn render_background(&mut self, background: &Background) {
-        for tile in background.iter() {
-            let sprite = &tile.sprite;
-            let position = &tile.position;
-
-            let start_pos_x = position.x as i32 * 8;
-            let start_pos_y = position.y as i32 * 8;
-
-            for (y, sprite_line) in sprite.to_vec().iter().enumerate() {
-                for (x, palette_number) in sprite_line.iter().enumerate() {
-                    let point_x = start_pos_x + x as i32;
-                    let point_y = start_pos_y + y as i32;
-                    let draw_point = Point::new(point_x, point_y);
-
-                    let palette = &tile.palettes.get(*palette_number as usize);
-                    let color = NES_COLORS[palette.get_palette_number() as usize];
-                    self.canvas.set_draw_color(Color::RGB(color[0], color[1], color[2]));
-                    self.canvas.draw_point(draw_point).unwrap();
-                }
+        for (y, line) in background.iter().enumerate() {
+            for (x, palette_number) in line.iter().enumerate() {
+                let color = NES_COLORS[*palette_number as usize];
+                self.canvas.set_draw_color(Color::RGB(color[0], color[1], color[2]));
+                self.canvas.draw_point(Point::new(x as i32, y as i32)).unwrap();
             }
         }
+    }
+
+    /// Draws the sprites over the background.
+    pub fn render_sprites(&mut self, sprites: &SpriteLayer) {
+        for pixel in sprites.iter() {
+            let color = NES_COLORS[pixel.palette_number as usize];
+            self.canvas.set_draw_color(Color::RGB(color[0], color[1], color[2]));
+            self.canvas.draw_point(Point::new(pixel.x as i32, pixel.y as i32)).unwrap();
+        }
+    }
 
+    pub fn present(&mut self) {
         self.canvas.present();
     }
 }